@@ -1,4 +1,3 @@
-#![feature(bench_black_box)]
 use nscl_evt::{NsclData, RingItem};
 use std::{fs::File, hint::black_box, io::Read};
 
@@ -9,16 +8,18 @@ fn main() {
 
     let d = NsclData::new(&v);
     d.into_iter().for_each(|e| {
+        let e = e.unwrap();
         let _ = black_box(e.bytes());
         let _ = black_box(e.size());
         let _ = black_box(e.type_id());
-        let bh = black_box(e.body_header());
+        let bh = black_box(e.body_header().unwrap());
         let _ = black_box(bh.bytes());
         let _ = black_box(bh.size());
         let _ = black_box(bh.timestamp());
         let _ = black_box(bh.source_id());
         let _ = black_box(bh.barrier_type());
-        match e.ring_item() {
+        let _ = black_box(bh.extension());
+        match e.ring_item().unwrap() {
             RingItem::BeginRun(ri) => {
                 let _ = black_box(ri.bytes());
                 let _ = black_box(ri.run_number());
@@ -122,6 +123,10 @@ fn main() {
             RingItem::UserItem(ri) => {
                 let _ = black_box(ri.bytes());
             }
+            RingItem::Unknown { type_id, source } => {
+                let _ = black_box(type_id);
+                let _ = black_box(source);
+            }
         }
     });
 }
@@ -1,25 +1,21 @@
-#![feature(bench_black_box)]
-use nscl_evt::{NsclData, RingItem};
-use std::{hint::black_box, io::Read};
+use nscl_evt::{NsclReader, RingItem};
+use std::hint::black_box;
 
 fn main() {
     let stdin = std::io::stdin();
-    let mut stdin = stdin.lock();
-    let mut v = Vec::new();
-    let _ = stdin.read_to_end(&mut v).unwrap();
-
-    let d = NsclData::new(&v);
-    d.into_iter().for_each(|e| {
+    let mut r = NsclReader::new(stdin.lock());
+    r.for_each_event(|e| {
         let _ = black_box(e.bytes());
         let _ = black_box(e.size());
         let _ = black_box(e.type_id());
-        let bh = black_box(e.body_header());
+        let bh = black_box(e.body_header().unwrap());
         let _ = black_box(bh.bytes());
         let _ = black_box(bh.size());
         let _ = black_box(bh.timestamp());
         let _ = black_box(bh.source_id());
         let _ = black_box(bh.barrier_type());
-        match e.ring_item() {
+        let _ = black_box(bh.extension());
+        match e.ring_item().unwrap() {
             RingItem::BeginRun(ri) => {
                 let _ = black_box(ri.bytes());
                 let _ = black_box(ri.run_number());
@@ -123,6 +119,11 @@ fn main() {
             RingItem::UserItem(ri) => {
                 let _ = black_box(ri.bytes());
             }
+            RingItem::Unknown { type_id, source } => {
+                let _ = black_box(type_id);
+                let _ = black_box(source);
+            }
         }
-    });
+    })
+    .unwrap();
 }
@@ -0,0 +1,56 @@
+use memmap::Mmap;
+use nscl_evt::{
+    scaler::{Accumulator, ChannelMap},
+    NsclData, OwnedEvent,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+mod scalers_print;
+
+fn add_events(rx: Receiver<OwnedEvent>, names: Option<ChannelMap>) -> HashMap<(u32, usize), u64> {
+    // The map also gives the counters' widths
+    let mut accumulator = match names {
+        Some(names) => Accumulator::new().channel_map(names),
+        None => Accumulator::new(),
+    };
+    for e in rx {
+        accumulator.observe(&e.as_ref()).unwrap();
+    }
+    accumulator.totals().collect()
+}
+
+fn main() {
+    let names = scalers_print::channel_map();
+
+    // Make sure all files are there before starting
+    let files = std::env::args()
+        .skip(1)
+        .map(|a| File::open(a).unwrap())
+        .collect::<Vec<_>>();
+
+    let (tx, rx) = channel();
+    let add_names = names.clone();
+    let add_handle = thread::spawn(move || add_events(rx, add_names));
+
+    for f in files {
+        let m = unsafe { Mmap::map(&f) }.unwrap();
+        let d = NsclData::new(&m);
+        // Begin run items are needed to know when counters are cleared
+        for e in d
+            .map(Result::unwrap)
+            .filter(|e| matches!(e.type_id(), 1 | 20))
+        {
+            // The map is dropped at the end of the loop, so the event has to be copied
+            tx.send(e.to_owned()).unwrap();
+        }
+    }
+
+    drop(tx);
+    let scaler_totals = add_handle.join().unwrap();
+    scalers_print::print(names.as_ref(), scaler_totals);
+}
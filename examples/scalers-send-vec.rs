@@ -9,17 +9,19 @@ use std::{
 
 mod scalers_print;
 
-fn add_map(rx: Receiver<Vec<((u32, usize), u32)>>) -> HashMap<(u32, usize), u32> {
+fn add_map(rx: Receiver<Vec<((u32, usize), u32)>>) -> HashMap<(u32, usize), u64> {
     let mut total = HashMap::new();
     for m in rx {
         for (k, v) in m {
-            *total.entry(k).or_insert(0) += v;
+            *total.entry(k).or_insert(0) += v as u64;
         }
     }
     total
 }
 
 fn main() {
+    let names = scalers_print::channel_map();
+
     // Make sure all files are there before starting
     let files = std::env::args()
         .skip(1)
@@ -32,9 +34,15 @@ fn main() {
     for f in files {
         let m = unsafe { Mmap::map(&f) }.unwrap();
         let d = NsclData::new(&m);
-        for e in d.filter(|e| e.type_id() == 20) {
-            let source_id = e.body_header().source_id().unwrap();
-            let scalers = e.ring_item().as_periodic_scalers().unwrap().scalers();
+        for e in d.map(Result::unwrap).filter(|e| e.type_id() == 20) {
+            let source_id = e.body_header().unwrap().source_id().unwrap();
+            let scalers = e
+                .ring_item()
+                .unwrap()
+                .as_periodic_scalers()
+                .unwrap()
+                .scalers()
+                .unwrap();
 
             let mut v = Vec::new();
             for (i, s) in scalers.into_iter().enumerate() {
@@ -46,5 +54,5 @@ fn main() {
 
     drop(tx);
     let scaler_totals = add_handle.join().unwrap();
-    scalers_print::print(scaler_totals);
+    scalers_print::print(names.as_ref(), scaler_totals);
 }
@@ -1,11 +1,18 @@
 use memmap::Mmap;
-use nscl_evt::NsclData;
-use std::{collections::HashMap, fs::File};
-
-mod scalers_print;
+use nscl_evt::{
+    scaler::{Accumulator, ChannelMap},
+    NsclData,
+};
+use std::fs::File;
 
 fn main() {
-    let mut scaler_totals = HashMap::new();
+    // Scalers are only listed when they can be listed by name, from the file in `SCALER_CHANNELS`
+    let names = std::env::var_os("SCALER_CHANNELS").map(|x| ChannelMap::from_file(x).unwrap());
+    let by_name = names.is_some();
+    let mut accumulator = match names {
+        Some(names) => Accumulator::new().channel_map(names),
+        None => Accumulator::new(),
+    };
 
     // Make sure all files are there before starting
     let files = std::env::args()
@@ -16,15 +23,18 @@ fn main() {
     for f in files {
         let m = unsafe { Mmap::map(&f) }.unwrap();
         let d = NsclData::new(&m);
-        for e in d.filter(|e| e.type_id() == 20) {
-            let source_id = e.body_header().source_id().unwrap();
-            let scalers = e.ring_item().as_periodic_scalers().unwrap().scalers();
+        for e in d.map(Result::unwrap) {
+            accumulator.observe(&e).unwrap();
+        }
+    }
 
-            for (i, s) in scalers.into_iter().enumerate() {
-                *scaler_totals.entry((source_id, i)).or_insert(0) += s;
-            }
+    if by_name {
+        for (label, total) in accumulator.named_totals() {
+            println!("{}: {}", label, total);
         }
     }
 
-    scalers_print::print(scaler_totals);
+    let total: u64 = accumulator.totals().map(|(_, x)| x).sum();
+
+    println!("{}", total);
 }
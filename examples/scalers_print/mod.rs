@@ -1,17 +1,23 @@
+use nscl_evt::scaler::ChannelMap;
 use std::collections::HashMap;
 
-//pub fn print(scaler_totals: HashMap<(u32, usize), u32>) {
-//    let mut scaler_totals = scaler_totals.into_iter().collect::<Vec<_>>();
-//    scaler_totals.sort_by_key(|((_, x), _)| *x);
-//    scaler_totals.sort_by_key(|((x, _), _)| *x);
-//
-//    for (k, v) in scaler_totals {
-//        println!("{:?}: {}", k, v);
-//    }
-//}
+/// The channel map named by `SCALER_CHANNELS`, if it's set.
+pub fn channel_map() -> Option<ChannelMap> {
+    let path = std::env::var_os("SCALER_CHANNELS")?;
+    Some(ChannelMap::from_file(path).unwrap())
+}
+
+pub fn print(names: Option<&ChannelMap>, scaler_totals: HashMap<(u32, usize), u64>) {
+    // Only list the scalers when they can be listed by name
+    if let Some(names) = names {
+        let mut listed = scaler_totals.iter().collect::<Vec<_>>();
+        listed.sort();
+        for (&(source_id, index), total) in listed {
+            println!("{}: {}", names.label(source_id, index), total);
+        }
+    }
 
-pub fn print(scaler_totals: HashMap<(u32, usize), u32>) {
-    let total: u32 = scaler_totals.into_iter().map(|(_k, v)| v).sum();
+    let total: u64 = scaler_totals.into_values().sum();
 
     println!("{}", total);
 }
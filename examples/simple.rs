@@ -1,121 +1,149 @@
 use memmap::Mmap;
 use nscl_evt::{NsclData, RingItem};
-use std::fs::File;
+use std::{error::Error, fs::File};
 
-fn main() {
-    let f = File::open("run-0446-00.evt").unwrap();
-    let m = unsafe { Mmap::map(&f) }.unwrap();
+fn main() -> Result<(), Box<dyn Error>> {
+    let f = File::open("run-0446-00.evt")?;
+    let m = unsafe { Mmap::map(&f) }?;
 
     let d = NsclData::new(&m);
+    println!("Data Format Version: {:?}", d.version());
+
     for e in d {
+        let e = e?;
         println!("Event Bytes: {:02x?}", e.bytes());
         println!("Event Size: {}", e.size());
         println!("Event Item Type: {}", e.type_id());
-        let bh = e.body_header();
+        let bh = e.body_header()?;
         println!("BodyHeader Bytes: {:02x?}", bh.bytes());
         println!("BodyHeader Size: {}", bh.size());
         println!("BodyHeader Timestamp: {:?}", bh.timestamp());
         println!("BodyHeader SourceID: {:?}", bh.source_id());
         println!("BodyHeader Barrier Type: {:?}", bh.barrier_type());
-        match e.ring_item() {
+        println!("BodyHeader Extension: {:02x?}", bh.extension());
+        match e.ring_item()? {
             RingItem::BeginRun(ri) => {
                 println!("BeginRun Bytes: {:02x?}", ri.bytes());
-                println!("BeginRun Run Number: {}", ri.run_number());
-                println!("BeginRun Time Offset: {}", ri.time_offset());
-                println!("BeginRun Timestamp: {}", ri.timestamp());
-                println!("BeginRun Offset Divisor: {}", ri.offset_divisor());
-                println!("BeginRun Title Bytes: {:02x?}", ri.title_bytes());
-                println!("BeginRun Title: {}", ri.title());
+                println!("BeginRun Run Number: {}", ri.run_number()?);
+                println!("BeginRun Time Offset: {}", ri.time_offset()?);
+                println!("BeginRun Timestamp: {}", ri.timestamp()?);
+                println!("BeginRun Offset Divisor: {}", ri.offset_divisor()?);
+                println!("BeginRun Original Source ID: {:?}", ri.original_sid()?);
+                println!("BeginRun Title Bytes: {:02x?}", ri.title_bytes()?);
+                println!("BeginRun Title: {}", ri.title()?);
             }
             RingItem::EndRun(ri) => {
                 println!("EndRun Bytes: {:02x?}", ri.bytes());
-                println!("EndRun Run Number: {}", ri.run_number());
-                println!("EndRun Time Offset: {}", ri.time_offset());
-                println!("EndRun Timestamp: {}", ri.timestamp());
-                println!("EndRun Offset Divisor: {}", ri.offset_divisor());
-                println!("EndRun Title Bytes: {:02x?}", ri.title_bytes());
-                println!("EndRun Title: {}", ri.title());
+                println!("EndRun Run Number: {}", ri.run_number()?);
+                println!("EndRun Time Offset: {}", ri.time_offset()?);
+                println!("EndRun Timestamp: {}", ri.timestamp()?);
+                println!("EndRun Offset Divisor: {}", ri.offset_divisor()?);
+                println!("EndRun Original Source ID: {:?}", ri.original_sid()?);
+                println!("EndRun Title Bytes: {:02x?}", ri.title_bytes()?);
+                println!("EndRun Title: {}", ri.title()?);
             }
             RingItem::PauseRun(ri) => {
                 println!("PauseRun Bytes: {:02x?}", ri.bytes());
-                println!("PauseRun Run Number: {}", ri.run_number());
-                println!("PauseRun Time Offset: {}", ri.time_offset());
-                println!("PauseRun Timestamp: {}", ri.timestamp());
-                println!("PauseRun Offset Divisor: {}", ri.offset_divisor());
-                println!("PauseRun Title Bytes: {:02x?}", ri.title_bytes());
-                println!("PauseRun Title: {}", ri.title());
+                println!("PauseRun Run Number: {}", ri.run_number()?);
+                println!("PauseRun Time Offset: {}", ri.time_offset()?);
+                println!("PauseRun Timestamp: {}", ri.timestamp()?);
+                println!("PauseRun Offset Divisor: {}", ri.offset_divisor()?);
+                println!("PauseRun Original Source ID: {:?}", ri.original_sid()?);
+                println!("PauseRun Title Bytes: {:02x?}", ri.title_bytes()?);
+                println!("PauseRun Title: {}", ri.title()?);
             }
             RingItem::ResumeRun(ri) => {
                 println!("ResumeRun Bytes: {:02x?}", ri.bytes());
-                println!("ResumeRun Run Number: {}", ri.run_number());
-                println!("ResumeRun Time Offset: {}", ri.time_offset());
-                println!("ResumeRun Timestamp: {}", ri.timestamp());
-                println!("ResumeRun Offset Divisor: {}", ri.offset_divisor());
-                println!("ResumeRun Title Bytes: {:02x?}", ri.title_bytes());
-                println!("ResumeRun Title: {}", ri.title());
+                println!("ResumeRun Run Number: {}", ri.run_number()?);
+                println!("ResumeRun Time Offset: {}", ri.time_offset()?);
+                println!("ResumeRun Timestamp: {}", ri.timestamp()?);
+                println!("ResumeRun Offset Divisor: {}", ri.offset_divisor()?);
+                println!("ResumeRun Original Source ID: {:?}", ri.original_sid()?);
+                println!("ResumeRun Title Bytes: {:02x?}", ri.title_bytes()?);
+                println!("ResumeRun Title: {}", ri.title()?);
             }
             RingItem::AbnormalEndRun(ri) => {
                 println!("AbnormalEndRun Bytes: {:02x?}", ri.bytes());
-                println!("AbnormalEndRun Run Number: {}", ri.run_number());
-                println!("AbnormalEndRun Time Offset: {}", ri.time_offset());
-                println!("AbnormalEndRun Timestamp: {}", ri.timestamp());
-                println!("AbnormalEndRun Offset Divisor: {}", ri.offset_divisor());
-                println!("AbnormalEndRun Title Bytes: {:02x?}", ri.title_bytes());
-                println!("AbnormalEndRun Title: {}", ri.title());
+                println!("AbnormalEndRun Run Number: {}", ri.run_number()?);
+                println!("AbnormalEndRun Time Offset: {}", ri.time_offset()?);
+                println!("AbnormalEndRun Timestamp: {}", ri.timestamp()?);
+                println!("AbnormalEndRun Offset Divisor: {}", ri.offset_divisor()?);
+                println!(
+                    "AbnormalEndRun Original Source ID: {:?}",
+                    ri.original_sid()?
+                );
+                println!("AbnormalEndRun Title Bytes: {:02x?}", ri.title_bytes()?);
+                println!("AbnormalEndRun Title: {}", ri.title()?);
             }
             RingItem::PacketTypes(ri) => {
                 println!("PacketTypes Bytes: {:02x?}", ri.bytes());
-                println!("PacketTypes Time Offset: {}", ri.time_offset());
-                println!("PacketTypes Timestamp: {}", ri.timestamp());
-                println!("PacketTypes String Count: {}", ri.string_count());
-                println!("PacketTypes Offset Divisor: {}", ri.offset_divisor());
-                println!("PacketTypes Strings Bytes: {:02x?}", ri.strings_bytes());
-                println!("PacketTypes Strings: {:?}", ri.strings());
+                println!("PacketTypes Time Offset: {}", ri.time_offset()?);
+                println!("PacketTypes Timestamp: {}", ri.timestamp()?);
+                println!("PacketTypes String Count: {}", ri.string_count()?);
+                println!("PacketTypes Offset Divisor: {}", ri.offset_divisor()?);
+                println!("PacketTypes Original Source ID: {:?}", ri.original_sid()?);
+                println!("PacketTypes Strings Bytes: {:02x?}", ri.strings_bytes()?);
+                println!("PacketTypes Strings: {:?}", ri.strings()?);
             }
             RingItem::MonitoredVariables(ri) => {
                 println!("MonitoredVariables Bytes: {:02x?}", ri.bytes());
-                println!("MonitoredVariables Time Offset: {}", ri.time_offset());
-                println!("MonitoredVariables Timestamp: {}", ri.timestamp());
-                println!("MonitoredVariables String Count: {}", ri.string_count());
-                println!("MonitoredVariables Offset Divisor: {}", ri.offset_divisor());
+                println!("MonitoredVariables Time Offset: {}", ri.time_offset()?);
+                println!("MonitoredVariables Timestamp: {}", ri.timestamp()?);
+                println!("MonitoredVariables String Count: {}", ri.string_count()?);
+                println!(
+                    "MonitoredVariables Offset Divisor: {}",
+                    ri.offset_divisor()?
+                );
+                println!(
+                    "MonitoredVariables Original Source ID: {:?}",
+                    ri.original_sid()?
+                );
                 println!(
                     "MonitoredVariables Strings Bytes: {:02x?}",
-                    ri.strings_bytes()
+                    ri.strings_bytes()?
                 );
-                println!("MonitoredVariables Strings: {:?}", ri.strings());
+                println!("MonitoredVariables Strings: {:?}", ri.strings()?);
             }
             RingItem::RingFormat(ri) => {
                 println!("RingFormat Bytes: {:02x?}", ri.bytes());
-                println!("Data Format: ({}, {})", ri.major(), ri.minor());
+                println!("Data Format: ({}, {})", ri.major()?, ri.minor()?);
             }
             RingItem::PeriodicScalers(ri) => {
                 println!("PeriodicScalers Bytes: {:02x?}", ri.bytes());
                 println!(
                     "PeriodicScalers Interval Start Offset: {}",
-                    ri.interval_start_offset()
+                    ri.interval_start_offset()?
                 );
                 println!(
                     "PeriodicScalers Interval End Offset: {}",
-                    ri.interval_end_offset()
+                    ri.interval_end_offset()?
                 );
-                println!("PeriodicScalers Timestamp: {}", ri.timestamp());
+                println!("PeriodicScalers Timestamp: {}", ri.timestamp()?);
                 println!(
                     "PeriodicScalers Interval Divisor: {}",
-                    ri.interval_divisor()
+                    ri.interval_divisor()?
                 );
-                println!("PeriodicScalers Scaler Count: {}", ri.scaler_count());
-                println!("PeriodicScalers Is Incremental: {}", ri.is_incremental());
-                println!("PeriodicScalers Scalers: {:?}", ri.scalers());
+                println!("PeriodicScalers Scaler Count: {}", ri.scaler_count()?);
+                println!("PeriodicScalers Is Incremental: {}", ri.is_incremental()?);
+                println!(
+                    "PeriodicScalers Original Source ID: {:?}",
+                    ri.original_sid()?
+                );
+                println!("PeriodicScalers Scalers: {:?}", ri.scalers()?);
             }
             RingItem::PhysicsEvent(ri) => {
                 println!("PhysicsEvent Bytes: {:02x?}", ri.bytes());
             }
             RingItem::PhysicsEventCount(ri) => {
                 println!("PhysicsEventCount Bytes: {:02x?}", ri.bytes());
-                println!("PhysicsEventCount Time Offset: {}", ri.time_offset());
-                println!("PhysicsEventCount Offset Divisor: {}", ri.offset_divisor());
-                println!("PhysicsEventCount Timestamp: {}", ri.timestamp());
-                println!("PhysicsEventCount Event Count: {}", ri.event_count());
+                println!("PhysicsEventCount Time Offset: {}", ri.time_offset()?);
+                println!("PhysicsEventCount Offset Divisor: {}", ri.offset_divisor()?);
+                println!(
+                    "PhysicsEventCount Original Source ID: {:?}",
+                    ri.original_sid()?
+                );
+                println!("PhysicsEventCount Timestamp: {}", ri.timestamp()?);
+                println!("PhysicsEventCount Event Count: {}", ri.event_count()?);
             }
             RingItem::EvbFragment(ri) => {
                 println!("EvbFragment Bytes: {:02x?}", ri.bytes());
@@ -125,14 +153,20 @@ fn main() {
             }
             RingItem::EvbGlomInfo(ri) => {
                 println!("EvbGlomInfo Bytes: {:02x?}", ri.bytes());
-                println!("EvbGlomInfo Coincident Ticks: {}", ri.coincident_ticks());
-                println!("EvbGlomInfo Is Building: {}", ri.is_building());
-                println!("EvbGlomInfo Timestamp Policy: {}", ri.timestamp_policy());
+                println!("EvbGlomInfo Coincident Ticks: {}", ri.coincident_ticks()?);
+                println!("EvbGlomInfo Is Building: {}", ri.is_building()?);
+                println!("EvbGlomInfo Timestamp Policy: {}", ri.timestamp_policy()?);
             }
             RingItem::UserItem(ri) => {
                 println!("UserItem Bytes: {:02x?}", ri.bytes());
             }
+            RingItem::Unknown { type_id, source } => {
+                println!("Unknown Type: {}", type_id);
+                println!("Unknown Bytes: {:02x?}", source);
+            }
         }
         println!();
     }
+
+    Ok(())
 }
@@ -1,39 +1,59 @@
-use std::array::TryFromSliceError;
+use crate::ParseError;
 
 pub(crate) trait TryFromSlice<const N: usize>
 where
     Self: Sized,
 {
-    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, TryFromSliceError>;
+    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, ParseError>;
+}
+
+/// Get the `N` bytes at `start`, or an error if they aren't all in `slice`.
+fn array_at<const N: usize>(slice: &[u8], start: usize) -> Result<[u8; N], ParseError> {
+    start
+        .checked_add(N)
+        .and_then(|end| slice.get(start..end))
+        .and_then(|x| x.try_into().ok())
+        .ok_or(ParseError::OutOfBounds {
+            offset: start,
+            len: N,
+        })
 }
 
 impl TryFromSlice<1> for u8 {
-    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, TryFromSliceError> {
-        slice[start..][..1]
-            .try_into()
-            .map(|x| Self::from_le_bytes(x))
+    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, ParseError> {
+        array_at(slice, start).map(Self::from_le_bytes)
     }
 }
 impl TryFromSlice<2> for u16 {
-    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, TryFromSliceError> {
-        slice[start..][..2]
-            .try_into()
-            .map(|x| Self::from_le_bytes(x))
+    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, ParseError> {
+        array_at(slice, start).map(Self::from_le_bytes)
     }
 }
 
 impl TryFromSlice<4> for u32 {
-    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, TryFromSliceError> {
-        slice[start..][..4]
-            .try_into()
-            .map(|x| Self::from_le_bytes(x))
+    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, ParseError> {
+        array_at(slice, start).map(Self::from_le_bytes)
     }
 }
 
 impl TryFromSlice<8> for u64 {
-    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, TryFromSliceError> {
-        slice[start..][..8]
-            .try_into()
-            .map(|x| Self::from_le_bytes(x))
+    fn try_from_slice(slice: &[u8], start: usize) -> Result<Self, ParseError> {
+        array_at(slice, start).map(Self::from_le_bytes)
     }
 }
+
+/// Get `len` bytes at `start`, or an error if they aren't all in `slice`.
+pub(crate) fn bytes_at(slice: &[u8], start: usize, len: usize) -> Result<&[u8], ParseError> {
+    start
+        .checked_add(len)
+        .and_then(|end| slice.get(start..end))
+        .ok_or(ParseError::OutOfBounds { offset: start, len })
+}
+
+/// Get the bytes from `start` to the end of `slice`, or an error if `start` is past the end.
+pub(crate) fn bytes_from(slice: &[u8], start: usize) -> Result<&[u8], ParseError> {
+    slice.get(start..).ok_or(ParseError::OutOfBounds {
+        offset: start,
+        len: 0,
+    })
+}
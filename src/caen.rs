@@ -0,0 +1,180 @@
+//! Decoding of CAEN V785 (ADC), V775 (TDC), and V792 (QDC) data.
+//!
+//! Each module's data is a block of 32-bit words: a header, one data word per channel that was
+//! read out, and an end of block word. The word type is in bits 24-26, and the geographical
+//! address of the module is in bits 27-31.
+
+use crate::{error, Cursor, ParseError, PhysicsEvent};
+use std::fmt;
+
+const HEADER: u32 = 2;
+const DATUM: u32 = 0;
+const END_OF_BLOCK: u32 = 4;
+const INVALID: u32 = 6;
+
+/// An error encountered while decoding CAEN data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// A word that isn't allowed where it is, e.g. a data word before a header.
+    UnexpectedWord(u32),
+    /// The data ended before the end of block word.
+    MissingEndOfBlock,
+    /// The number of data words doesn't match the count in the header.
+    CountMismatch {
+        header: u32,
+        data: u32,
+    },
+    /// A word has a different geographical address than its header.
+    GeoMismatch {
+        header: u32,
+        word: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::UnexpectedWord(x) => write!(f, "unexpected word {:#010x}", x),
+            Self::MissingEndOfBlock => write!(f, "missing end of block"),
+            Self::CountMismatch { header, data } => write!(
+                f,
+                "header count is {}, but there are {} data words",
+                header, data
+            ),
+            Self::GeoMismatch { header, word } => {
+                write!(f, "header geo is {}, but a word has geo {}", header, word)
+            }
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// A single channel's conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datum {
+    pub channel: u32,
+    pub value: u32,
+    pub overflow: bool,
+    pub underflow: bool,
+}
+
+/// The data from one module for one event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub geo: u32,
+    pub crate_id: u32,
+    pub event_counter: u32,
+    pub data: Vec<Datum>,
+}
+
+impl Block {
+    /// The conversion for `channel`, if it was read out.
+    pub fn channel(&self, channel: u32) -> Option<&Datum> {
+        self.data.iter().find(|x| x.channel == channel)
+    }
+}
+
+/// An iterator over the blocks in some data, skipping any invalid datum (filler) words between
+/// blocks.
+#[derive(Debug, Clone, Copy)]
+pub struct Blocks<'s> {
+    cursor: Cursor<'s>,
+}
+
+impl<'s> Blocks<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self {
+            cursor: Cursor::new(source),
+        }
+    }
+
+    /// The blocks in a physics event body, after its 32-bit size (in 16-bit words, including the
+    /// size itself).
+    pub fn from_physics_event(event: &PhysicsEvent<'s>) -> Result<Self, Error> {
+        let mut cursor = event.cursor();
+        let size = cursor.read_u32()? as usize * 2;
+        Ok(Self::new(cursor.read_bytes(size.saturating_sub(4))?))
+    }
+
+    /// The bytes after the blocks that have been read so far.
+    pub fn rest(&self) -> &'s [u8] {
+        self.cursor.rest()
+    }
+
+    fn read_block(&mut self, header: u32) -> Result<Block, Error> {
+        let geo = geo_of(header);
+        let crate_id = (header >> 16) & 0xff;
+        let count = (header >> 8) & 0x3f;
+        let mut data = Vec::new();
+        loop {
+            let word = self
+                .cursor
+                .read_u32()
+                .map_err(|_| Error::MissingEndOfBlock)?;
+            if geo_of(word) != geo {
+                return Err(Error::GeoMismatch {
+                    header: geo,
+                    word: geo_of(word),
+                });
+            }
+            match word_type(word) {
+                DATUM => data.push(Datum {
+                    channel: (word >> 16) & 0x1f,
+                    value: word & 0xfff,
+                    overflow: word & (1 << 12) != 0,
+                    underflow: word & (1 << 13) != 0,
+                }),
+                END_OF_BLOCK => {
+                    if data.len() as u32 != count {
+                        return Err(Error::CountMismatch {
+                            header: count,
+                            data: data.len() as u32,
+                        });
+                    }
+                    return Ok(Block {
+                        geo,
+                        crate_id,
+                        event_counter: word & 0xff_ffff,
+                        data,
+                    });
+                }
+                _ => return Err(Error::UnexpectedWord(word)),
+            }
+        }
+    }
+}
+
+impl<'s> Iterator for Blocks<'s> {
+    type Item = Result<Block, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.cursor.is_empty() {
+            let result = match self.cursor.read_u32() {
+                Ok(word) => match word_type(word) {
+                    INVALID => continue,
+                    HEADER => self.read_block(word),
+                    _ => Err(Error::UnexpectedWord(word)),
+                },
+                Err(e) => Err(e.into()),
+            };
+            if result.is_err() {
+                // Once a block is bad, its count can't be trusted, so there's no telling which of
+                // the following words are left over from it
+                self.cursor = Cursor::new(&[]);
+            }
+            return Some(result);
+        }
+        None
+    }
+}
+
+fn word_type(word: u32) -> u32 {
+    (word >> 24) & 0x7
+}
+
+fn geo_of(word: u32) -> u32 {
+    word >> 27
+}
@@ -0,0 +1,62 @@
+use crate::{Event, ParseError, StateChange};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A clock that maps items to calendar times, using the time of the last begin run item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunClock {
+    start: Option<SystemTime>,
+}
+
+impl RunClock {
+    /// A clock that hasn't seen the start of a run yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the clock at the time of a begin run item.
+    pub fn start_run(&mut self, begin_run: &StateChange) -> Result<(), ParseError> {
+        let time = begin_run.system_time()?;
+        // The begin run item is usually at offset 0, but may not be
+        let elapsed = begin_run.elapsed()?;
+        self.start = Some(time.checked_sub(elapsed).unwrap_or(UNIX_EPOCH));
+        Ok(())
+    }
+
+    /// Start the clock if `event` is a begin run item.
+    pub fn observe(&mut self, event: &Event) -> Result<(), ParseError> {
+        if let Some(begin_run) = event.ring_item()?.as_begin_run() {
+            self.start_run(&begin_run)?;
+        }
+        Ok(())
+    }
+
+    /// The time the run started, if a begin run item has been seen.
+    pub fn start(&self) -> Option<SystemTime> {
+        self.start
+    }
+
+    /// The calendar time of `event`, if it has a time offset and the run has started.
+    ///
+    /// Unlike the items' own timestamps, which are in whole seconds, this keeps the precision of
+    /// the time offset.
+    pub fn time(&self, event: &Event) -> Result<Option<SystemTime>, ParseError> {
+        let start = match self.start {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        Ok(event.ring_item()?.elapsed()?.map(|x| start + x))
+    }
+}
+
+/// Convert a time offset to a `Duration`, treating a divisor of 0 as 1.
+pub(crate) fn offset_duration(offset: u32, divisor: u32) -> Duration {
+    let divisor = divisor.max(1) as u64;
+    let offset = offset as u64;
+    let nanos = (offset % divisor) * 1_000_000_000 / divisor;
+    Duration::new(offset / divisor, nanos as u32)
+}
+
+/// Convert a Unix timestamp to a `SystemTime`.
+pub(crate) fn system_time(timestamp: u32) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+}
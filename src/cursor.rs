@@ -0,0 +1,77 @@
+use crate::{
+    bits::{bytes_at, TryFromSlice},
+    ParseError,
+};
+
+/// A cursor for reading little-endian values from the front of some bytes.
+///
+/// Reads past the end return an error and leave the cursor where it was.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'s> {
+    source: &'s [u8],
+    position: usize,
+}
+
+impl<'s> Cursor<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+
+    /// The number of bytes read or skipped so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.source.len() - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// The bytes left to read.
+    pub fn rest(&self) -> &'s [u8] {
+        &self.source[self.position..]
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result<(), ParseError> {
+        self.read_bytes(n).map(|_| ())
+    }
+
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'s [u8], ParseError> {
+        let bytes = bytes_at(self.source, self.position, n)?;
+        self.position += n;
+        Ok(bytes)
+    }
+
+    pub fn peek_u16(&self) -> Result<u16, ParseError> {
+        u16::try_from_slice(self.source, self.position)
+    }
+
+    pub fn peek_u32(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, self.position)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, ParseError> {
+        let x = self.peek_u16()?;
+        self.position += 2;
+        Ok(x)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, ParseError> {
+        let x = self.peek_u32()?;
+        self.position += 4;
+        Ok(x)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, ParseError> {
+        let x = u64::try_from_slice(self.source, self.position)?;
+        self.position += 8;
+        Ok(x)
+    }
+}
@@ -0,0 +1,269 @@
+//! Decoding of DDAS (XIA Pixie-16) hits.
+//!
+//! A DDAS physics event body (usually the payload of one event builder fragment) is laid out as:
+//!
+//! * the size of the body in 16-bit words, including the size itself (32 bits)
+//! * the module identification word: ADC frequency in MHz (bits 0-15), ADC resolution in bits
+//!   (bits 16-23), and hardware revision (bits 24-31)
+//! * the Pixie-16 list-mode data for one hit
+
+use crate::{error, Cursor, ParseError, PhysicsEvent};
+use std::fmt;
+
+/// An error encountered while decoding a DDAS hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// The header length isn't one of the lengths that the firmware produces.
+    BadHeaderLength(u32),
+    /// The event length is shorter than the header.
+    BadEventLength {
+        header_length: u32,
+        event_length: u32,
+    },
+    /// The trace doesn't fill the rest of the event.
+    BadTraceLength {
+        trace_length: u32,
+        words: u32,
+    },
+    /// The ADC frequency isn't one that the time calibration is known for.
+    UnknownAdcFrequency(u16),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::BadHeaderLength(x) => write!(f, "bad header length {}", x),
+            Self::BadEventLength {
+                header_length,
+                event_length,
+            } => write!(
+                f,
+                "bad event length {} with header length {}",
+                event_length, header_length
+            ),
+            Self::BadTraceLength {
+                trace_length,
+                words,
+            } => write!(
+                f,
+                "trace length {} doesn't fit in {} words",
+                trace_length, words
+            ),
+            Self::UnknownAdcFrequency(x) => write!(f, "unknown ADC frequency {} MHz", x),
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// The module that produced a hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Module {
+    /// The ADC frequency in MHz.
+    pub adc_frequency: u16,
+    /// The ADC resolution in bits.
+    pub adc_bits: u8,
+    /// The hardware revision (e.g. 0xF for Rev. F).
+    pub revision: u8,
+}
+
+impl Module {
+    pub fn from_word(word: u32) -> Self {
+        Self {
+            adc_frequency: word as u16,
+            adc_bits: (word >> 16) as u8,
+            revision: (word >> 24) as u8,
+        }
+    }
+}
+
+/// A decoded Pixie-16 hit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub module: Module,
+    pub crate_id: u32,
+    pub slot: u32,
+    pub channel: u32,
+    /// The number of 32-bit words in the header.
+    pub header_length: u32,
+    /// The number of 32-bit words in the whole hit, including the header.
+    pub event_length: u32,
+    /// Whether the module reported a pileup or other problem.
+    pub finish_code: bool,
+    /// The 48-bit timestamp, in clock ticks.
+    pub timestamp: u64,
+    pub cfd_fraction: u32,
+    pub cfd_trigger_source: u32,
+    pub cfd_fail: bool,
+    pub energy: u32,
+    /// The number of 16-bit samples in the trace.
+    pub trace_length: u32,
+    /// Whether the ADC went out of range during the trace.
+    pub out_of_range: bool,
+    /// Trailing, leading, gap, and baseline sums.
+    pub energy_sums: Option<[u32; 4]>,
+    pub qdc_sums: Option<[u32; 8]>,
+    /// The 48-bit external timestamp.
+    pub external_timestamp: Option<u64>,
+    pub trace: Vec<u16>,
+}
+
+impl Hit {
+    /// Decode the hit in a physics event.
+    pub fn from_physics_event(event: &PhysicsEvent) -> Result<Self, Error> {
+        Self::decode(event.bytes())
+    }
+
+    /// Decode the hit in a DDAS physics event body.
+    pub fn decode(body: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(body);
+        // The size is implied by the event length, so it isn't needed
+        cursor.skip(4)?;
+        let module = Module::from_word(cursor.read_u32()?);
+
+        let word = cursor.read_u32()?;
+        let channel = word & 0xf;
+        let slot = (word >> 4) & 0xf;
+        let crate_id = (word >> 8) & 0xf;
+        let header_length = (word >> 12) & 0x1f;
+        let event_length = (word >> 17) & 0x3fff;
+        let finish_code = word >> 31 != 0;
+        if event_length < header_length {
+            return Err(Error::BadEventLength {
+                header_length,
+                event_length,
+            });
+        }
+
+        let timestamp_low = cursor.read_u32()?;
+        let word = cursor.read_u32()?;
+        let timestamp = (((word & 0xffff) as u64) << 32) | timestamp_low as u64;
+        let (cfd_fraction, cfd_trigger_source, cfd_fail) = match module.adc_frequency {
+            100 if module.revision < 0xf => (word >> 16, 0, false),
+            100 => ((word >> 16) & 0x7fff, 0, word >> 31 != 0),
+            250 => ((word >> 16) & 0x3fff, (word >> 30) & 1, word >> 31 != 0),
+            500 => {
+                let trigger_source = (word >> 29) & 0x7;
+                ((word >> 16) & 0x1fff, trigger_source, trigger_source == 7)
+            }
+            x => return Err(Error::UnknownAdcFrequency(x)),
+        };
+
+        let word = cursor.read_u32()?;
+        let energy = word & 0xffff;
+        let trace_length = (word >> 16) & 0x7fff;
+        let out_of_range = word >> 31 != 0;
+
+        let (has_energy_sums, has_qdc_sums, has_external_timestamp) = match header_length {
+            4 => (false, false, false),
+            6 => (false, false, true),
+            8 => (true, false, false),
+            10 => (true, false, true),
+            12 => (false, true, false),
+            14 => (false, true, true),
+            16 => (true, true, false),
+            18 => (true, true, true),
+            x => return Err(Error::BadHeaderLength(x)),
+        };
+        let energy_sums = if has_energy_sums {
+            Some(read_words(&mut cursor)?)
+        } else {
+            None
+        };
+        let qdc_sums = if has_qdc_sums {
+            Some(read_words(&mut cursor)?)
+        } else {
+            None
+        };
+        let external_timestamp = if has_external_timestamp {
+            let low = cursor.read_u32()? as u64;
+            let high = (cursor.read_u32()? & 0xffff) as u64;
+            Some((high << 32) | low)
+        } else {
+            None
+        };
+
+        // Samples are packed two to a word
+        let words = event_length - header_length;
+        if trace_length != 2 * words {
+            return Err(Error::BadTraceLength {
+                trace_length,
+                words,
+            });
+        }
+        let trace = (0..trace_length)
+            .map(|_| cursor.read_u16())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            module,
+            crate_id,
+            slot,
+            channel,
+            header_length,
+            event_length,
+            finish_code,
+            timestamp,
+            cfd_fraction,
+            cfd_trigger_source,
+            cfd_fail,
+            energy,
+            trace_length,
+            out_of_range,
+            energy_sums,
+            qdc_sums,
+            external_timestamp,
+            trace,
+        })
+    }
+
+    /// The timestamp in ns, without the CFD correction.
+    pub fn coarse_time(&self) -> f64 {
+        // 250 MHz modules count at half their sampling rate
+        let period = match self.module.adc_frequency {
+            250 => 8.0,
+            _ => 10.0,
+        };
+        self.timestamp as f64 * period
+    }
+
+    /// The time in ns, including the CFD correction (unless the CFD failed).
+    pub fn time(&self) -> f64 {
+        if self.cfd_fail {
+            return self.coarse_time();
+        }
+        let fraction = self.cfd_fraction as f64;
+        let correction = match self.module.adc_frequency {
+            100 if self.module.revision < 0xf => fraction / 65536.0 * 10.0,
+            100 => fraction / 32768.0 * 10.0,
+            250 => (fraction / 16384.0 - self.cfd_trigger_source as f64) * 4.0,
+            500 => (fraction / 8192.0 + self.cfd_trigger_source as f64 - 1.0) * 2.0,
+            // Checked in `decode`
+            _ => 0.0,
+        };
+        self.coarse_time() + correction
+    }
+}
+
+/// Decode the hits in every fragment of a built physics event.
+pub fn hits(event: &PhysicsEvent) -> Result<Vec<Hit>, Error> {
+    event
+        .fragments()?
+        .map(|fragment| {
+            let event = fragment?.event()?;
+            let body = event.ring_item()?.bytes();
+            Hit::decode(body)
+        })
+        .collect()
+}
+
+fn read_words<const N: usize>(cursor: &mut Cursor) -> Result<[u32; N], ParseError> {
+    let mut words = [0; N];
+    for word in &mut words {
+        *word = cursor.read_u32()?;
+    }
+    Ok(words)
+}
@@ -0,0 +1,294 @@
+//! Dispatching physics events to decoders by source id or packet tag.
+//!
+//! A [`Registry`] holds decoders keyed by the source id in the body header, and by packet tag. Each
+//! physics event is walked down to its leaves (so the fragments of built events are handled
+//! individually), and each leaf is given to the decoder for its source id. If there isn't one, the
+//! top-level packets in the leaf are given to the decoders for their tags. All of the decoders
+//! for an event add to one output value, which starts as `T::default()`.
+//!
+//! Besides closures, there are decoders for the formats in this crate: [`Ddas`], [`Caen`],
+//! [`Mesytec`], and [`V1190`]. Each adds what it decodes to an output that implements [`Extend`],
+//! so an output that holds several kinds of data needs an `Extend` implementation for each.
+
+use crate::{
+    caen, ddas, mesytec,
+    packet::{Packet, Packets},
+    v1190, Cursor, Event, FragmentWalker, NsclData, ParseError,
+};
+use std::collections::HashMap;
+
+/// Something that decodes the body of a physics event (or packet) into an output value.
+///
+/// This is implemented for closures of the form `|data: &[u8], output: &mut T| -> Result<(), E>`.
+pub trait Decoder<T, E> {
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E>;
+}
+
+impl<T, E, F> Decoder<T, E> for F
+where
+    F: FnMut(&[u8], &mut T) -> Result<(), E>,
+{
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E> {
+        self(data, output)
+    }
+}
+
+/// A set of decoders, and the rules for dispatching events to them.
+///
+/// Leaves with no matching decoder are ignored, including leaves that aren't made of packets when
+/// there are decoders for packet tags.
+pub struct Registry<T, E> {
+    source_ids: HashMap<u32, Box<dyn Decoder<T, E>>>,
+    packet_tags: HashMap<u16, Box<dyn Decoder<T, E>>>,
+    walker: FragmentWalker,
+}
+
+impl<T, E> Registry<T, E>
+where
+    T: Default,
+    E: From<ParseError>,
+{
+    pub fn new() -> Self {
+        Self {
+            source_ids: HashMap::new(),
+            packet_tags: HashMap::new(),
+            walker: FragmentWalker::new(),
+        }
+    }
+
+    /// Decode the body of leaves with source id `source_id` with `decoder`.
+    pub fn source_id(mut self, source_id: u32, decoder: impl Decoder<T, E> + 'static) -> Self {
+        self.source_ids.insert(source_id, Box::new(decoder));
+        self
+    }
+
+    /// Decode the body of packets with tag `tag` with `decoder`.
+    pub fn packet_tag(mut self, tag: u16, decoder: impl Decoder<T, E> + 'static) -> Self {
+        self.packet_tags.insert(tag, Box::new(decoder));
+        self
+    }
+
+    /// Use `walker` to find the leaves of built events, e.g. to limit how far it descends.
+    pub fn walker(mut self, walker: FragmentWalker) -> Self {
+        self.walker = walker;
+        self
+    }
+
+    /// Decode a physics event, or return `None` for any other item.
+    pub fn decode_event(&mut self, event: Event<'_>) -> Result<Option<T>, E> {
+        if event.ring_item()?.as_physics_event().is_none() {
+            return Ok(None);
+        }
+        let mut output = T::default();
+        for leaf in self.walker.walk(event)? {
+            let ring_item = leaf.event.ring_item()?;
+            let physics_event = match ring_item.as_physics_event() {
+                Some(x) => x,
+                None => continue,
+            };
+            let source_id = leaf.path.last().and_then(|x| x.source_id);
+            if let Some(decoder) = source_id.and_then(|x| self.source_ids.get_mut(&x)) {
+                decoder.decode(physics_event.bytes(), &mut output)?;
+            } else if !self.packet_tags.is_empty() {
+                // The body might be from a source that just doesn't have a decoder
+                let packets = match Packets::from_physics_event(&physics_event)
+                    .and_then(|x| x.collect::<Result<Vec<Packet>, _>>())
+                {
+                    Ok(x) => x,
+                    Err(_) => continue,
+                };
+                for packet in packets {
+                    if let Some(decoder) = self.packet_tags.get_mut(&packet.tag()) {
+                        decoder.decode(packet.body(), &mut output)?;
+                    }
+                }
+            }
+        }
+        Ok(Some(output))
+    }
+
+    /// Decode every physics event in `data`.
+    pub fn decode_events<'r, 's>(&'r mut self, data: NsclData<'s>) -> Decoded<'r, 's, T, E> {
+        Decoded {
+            registry: self,
+            data,
+        }
+    }
+}
+
+impl<T, E> Default for Registry<T, E>
+where
+    T: Default,
+    E: From<ParseError>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the decoded physics events in some data.
+pub struct Decoded<'r, 's, T, E> {
+    registry: &'r mut Registry<T, E>,
+    data: NsclData<'s>,
+}
+
+impl<'r, 's, T, E> Iterator for Decoded<'r, 's, T, E>
+where
+    T: Default,
+    E: From<ParseError>,
+{
+    type Item = Result<T, E>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let result = match self.data.next()? {
+                Ok(event) => self.registry.decode_event(event),
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(Some(output)) => return Some(Ok(output)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A decoder of the DDAS hit in a leaf's body.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ddas;
+
+impl<T, E> Decoder<T, E> for Ddas
+where
+    T: Extend<ddas::Hit>,
+    E: From<ddas::Error>,
+{
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E> {
+        output.extend([ddas::Hit::decode(data)?]);
+        Ok(())
+    }
+}
+
+/// A decoder of CAEN V785, V775, or V792 blocks.
+#[derive(Debug, Clone, Copy)]
+pub struct Caen {
+    size_word: bool,
+}
+
+impl Caen {
+    /// Decode a leaf's body, which starts with its 32-bit size (in 16-bit words).
+    pub fn new() -> Self {
+        Self { size_word: true }
+    }
+
+    /// Decode data with no size word, e.g. the body of a packet.
+    pub fn without_size() -> Self {
+        Self { size_word: false }
+    }
+}
+
+impl Default for Caen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> Decoder<T, E> for Caen
+where
+    T: Extend<caen::Block>,
+    E: From<caen::Error>,
+{
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E> {
+        let data = after_size(data, self.size_word).map_err(caen::Error::from)?;
+        let blocks = caen::Blocks::new(data).collect::<Result<Vec<_>, _>>()?;
+        output.extend(blocks);
+        Ok(())
+    }
+}
+
+/// A decoder of Mesytec MADC-32, MQDC-32, or MTDC-32 blocks.
+#[derive(Debug, Clone, Copy)]
+pub struct Mesytec {
+    module: mesytec::Module,
+    size_word: bool,
+}
+
+impl Mesytec {
+    /// Decode a leaf's body, which starts with its 32-bit size (in 16-bit words).
+    pub fn new(module: mesytec::Module) -> Self {
+        Self {
+            module,
+            size_word: true,
+        }
+    }
+
+    /// Decode data with no size word, e.g. the body of a packet.
+    pub fn without_size(module: mesytec::Module) -> Self {
+        Self {
+            module,
+            size_word: false,
+        }
+    }
+}
+
+impl<T, E> Decoder<T, E> for Mesytec
+where
+    T: Extend<mesytec::Block>,
+    E: From<mesytec::Error>,
+{
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E> {
+        let data = after_size(data, self.size_word).map_err(mesytec::Error::from)?;
+        let blocks = mesytec::Blocks::new(data, self.module).collect::<Result<Vec<_>, _>>()?;
+        output.extend(blocks);
+        Ok(())
+    }
+}
+
+/// A decoder of CAEN V1190 or V1290 events.
+#[derive(Debug, Clone, Copy)]
+pub struct V1190 {
+    model: v1190::Model,
+    size_word: bool,
+}
+
+impl V1190 {
+    /// Decode a leaf's body, which starts with its 32-bit size (in 16-bit words).
+    pub fn new(model: v1190::Model) -> Self {
+        Self {
+            model,
+            size_word: true,
+        }
+    }
+
+    /// Decode data with no size word, e.g. the body of a packet.
+    pub fn without_size(model: v1190::Model) -> Self {
+        Self {
+            model,
+            size_word: false,
+        }
+    }
+}
+
+impl<T, E> Decoder<T, E> for V1190
+where
+    T: Extend<v1190::Event>,
+    E: From<ParseError> + From<v1190::Error>,
+{
+    fn decode(&mut self, data: &[u8], output: &mut T) -> Result<(), E> {
+        let words = after_size(data, self.size_word)?
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]));
+        let events = v1190::Events::new(words, self.model).collect::<Result<Vec<_>, _>>()?;
+        output.extend(events);
+        Ok(())
+    }
+}
+
+/// The data after a 32-bit size (in 16-bit words, including the size itself), if there is one.
+fn after_size(data: &[u8], size_word: bool) -> Result<&[u8], ParseError> {
+    if !size_word {
+        return Ok(data);
+    }
+    let mut cursor = Cursor::new(data);
+    let size = cursor.read_u32()? as usize * 2;
+    cursor.read_bytes(size.saturating_sub(4))
+}
@@ -0,0 +1,117 @@
+use std::{fmt, str::Utf8Error};
+
+/// An error encountered while parsing NSCL data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The item's size word claims more bytes than are available.
+    TruncatedItem { size: usize, available: usize },
+    /// The item's size word is zero, so there is no way to advance past it.
+    ZeroSize,
+    /// The body header size is not one that is understood.
+    BadBodyHeaderSize(u32),
+    /// A string field is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// A field lies (at least partially) outside of the bytes available.
+    OutOfBounds { offset: usize, len: usize },
+    /// The item's type isn't a known ring item type, so it is taken to be garbage while
+    /// resynchronizing.
+    UnknownType(u32),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TruncatedItem { size, available } => write!(
+                f,
+                "item size is {} bytes, but only {} bytes are available",
+                size, available
+            ),
+            Self::ZeroSize => write!(f, "item size is 0"),
+            Self::BadBodyHeaderSize(size) => write!(f, "bad body header size {}", size),
+            Self::InvalidUtf8(e) => write!(f, "invalid UTF-8: {}", e),
+            Self::OutOfBounds { offset, len } => write!(
+                f,
+                "field of {} bytes at offset {} is out of bounds",
+                len, offset
+            ),
+            Self::UnknownType(type_id) => write!(f, "unknown item type {}", type_id),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for ParseError {
+    fn from(e: Utf8Error) -> Self {
+        Self::InvalidUtf8(e)
+    }
+}
+
+/// An error encountered while reading NSCL data from a stream.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReadError {
+    Io(std::io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Parse(e) => write!(f, "parse error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Implement `From<ParseError>` and [`std::error::Error`] for a decoder's error type.
+///
+/// The type must have a `Parse(ParseError)` variant, which is the only one with a source.
+macro_rules! impl_parse_wrapper {
+    ($error:ty) => {
+        impl From<$crate::ParseError> for $error {
+            fn from(e: $crate::ParseError) -> Self {
+                Self::Parse(e)
+            }
+        }
+
+        impl std::error::Error for $error {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    Self::Parse(e) => Some(e),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+pub(crate) use impl_parse_wrapper;
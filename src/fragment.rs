@@ -0,0 +1,189 @@
+use crate::{bits::TryFromSlice, Event, FormatVersion, ParseError};
+
+/// The size of a fragment header, in bytes.
+const HEADER_SIZE: usize = 20;
+
+/// An iterator over the fragments in a built event.
+///
+/// This is created by [`PhysicsEvent::fragments`](crate::PhysicsEvent::fragments).
+#[derive(Debug, Clone, Copy)]
+pub struct Fragments<'s> {
+    source: &'s [u8],
+    version: FormatVersion,
+}
+
+impl<'s> Fragments<'s> {
+    /// Iterate over the fragments in `source`, which is the built event body after its size.
+    pub fn new(source: &'s [u8]) -> Self {
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
+    }
+}
+
+impl<'s> Iterator for Fragments<'s> {
+    type Item = Result<Fragment<'s>, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.source.is_empty() {
+            None
+        } else {
+            match Fragment::with_version(self.source, self.version) {
+                Ok(fragment) => {
+                    self.source = &self.source[fragment.bytes().len()..];
+                    Some(Ok(fragment))
+                }
+                Err(e) => {
+                    // The next header is found from this one's payload size, which can't be
+                    // trusted now
+                    self.source = &[];
+                    Some(Err(e))
+                }
+            }
+        }
+    }
+}
+
+/// A fragment in a built event: a header followed by a payload.
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment<'s> {
+    source: &'s [u8],
+    version: FormatVersion,
+}
+
+impl<'s> Fragment<'s> {
+    pub fn new(source: &'s [u8]) -> Result<Self, ParseError> {
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Result<Self, ParseError> {
+        let size = HEADER_SIZE + u32::try_from_slice(source, 12)? as usize;
+        let source = source.get(..size).ok_or(ParseError::TruncatedItem {
+            size,
+            available: source.len(),
+        })?;
+        Ok(Self { source, version })
+    }
+
+    pub fn bytes(&self) -> &'s [u8] {
+        self.source
+    }
+
+    // The header accessors can't fail, since the size is checked in `new`
+
+    pub fn timestamp(&self) -> u64 {
+        u64::try_from_slice(self.source, 0).unwrap()
+    }
+
+    pub fn source_id(&self) -> u32 {
+        u32::try_from_slice(self.source, 8).unwrap()
+    }
+
+    pub fn payload_size(&self) -> u32 {
+        u32::try_from_slice(self.source, 12).unwrap()
+    }
+
+    pub fn barrier_type(&self) -> u32 {
+        u32::try_from_slice(self.source, 16).unwrap()
+    }
+
+    pub fn payload(&self) -> &'s [u8] {
+        &self.source[HEADER_SIZE..]
+    }
+
+    /// The payload, which is normally a complete ring item.
+    pub fn event(&self) -> Result<Event<'s>, ParseError> {
+        Event::with_version(self.payload(), self.version)
+    }
+}
+
+/// The body header fields of one level of nesting in a built event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub source_id: Option<u32>,
+    pub timestamp: Option<u64>,
+}
+
+/// An event that wasn't descended into any further.
+#[derive(Debug, Clone)]
+pub struct Leaf<'s> {
+    /// The levels from the outermost event down to (and including) this one.
+    pub path: Vec<Level>,
+    pub event: Event<'s>,
+}
+
+/// A walker that descends through events built by (possibly chained) event builders.
+///
+/// A physics event is treated as built if its whole body parses as fragments, each holding
+/// exactly one ring item.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FragmentWalker {
+    max_depth: Option<usize>,
+}
+
+impl FragmentWalker {
+    /// A walker that descends as far as possible.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop descending after `max_depth` levels of fragments, so 0 doesn't descend at all.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Find all of the leaves in `event`, in order.
+    pub fn walk<'s>(&self, event: Event<'s>) -> Result<Vec<Leaf<'s>>, ParseError> {
+        let mut leaves = Vec::new();
+        self.walk_into(event, &mut Vec::new(), &mut leaves)?;
+        Ok(leaves)
+    }
+
+    fn walk_into<'s>(
+        &self,
+        event: Event<'s>,
+        path: &mut Vec<Level>,
+        leaves: &mut Vec<Leaf<'s>>,
+    ) -> Result<(), ParseError> {
+        let bh = event.body_header()?;
+        path.push(Level {
+            source_id: bh.source_id(),
+            timestamp: bh.timestamp(),
+        });
+        let depth = path.len() - 1;
+        match built_events(event) {
+            Some(events) if self.max_depth.is_none_or(|x| depth < x) => {
+                for event in events {
+                    self.walk_into(event, path, leaves)?;
+                }
+            }
+            _ => leaves.push(Leaf {
+                path: path.clone(),
+                event,
+            }),
+        }
+        path.pop();
+        Ok(())
+    }
+}
+
+/// The events in the fragments of `event`, if it's a built event.
+fn built_events(event: Event<'_>) -> Option<Vec<Event<'_>>> {
+    let fragments = event
+        .ring_item()
+        .ok()?
+        .as_physics_event()?
+        .fragments()
+        .ok()?;
+    let events = fragments
+        .map(|fragment| {
+            let fragment = fragment.ok()?;
+            let event = fragment.event().ok()?;
+            // The payload must be exactly one ring item
+            (event.bytes().len() == fragment.payload().len()).then_some(event)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!events.is_empty()).then_some(events)
+}
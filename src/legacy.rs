@@ -0,0 +1,300 @@
+//! Reading of the fixed-size buffers used by NSCLDAQ 8.x and earlier.
+//!
+//! Rather than having a parallel set of views, the entities in a buffer are converted to 11.x
+//! ring items, which can then be read with [`NsclData`](crate::NsclData) like any other data.
+
+use crate::{
+    bits::{bytes_at, bytes_from, TryFromSlice},
+    ParseError,
+};
+
+/// The size of the buffer header, in bytes.
+const HEADER_SIZE: usize = 32;
+
+/// The most common buffer size, in bytes.
+pub const DEFAULT_BUFFER_SIZE: usize = 8192;
+
+/// An iterator over the buffers in 8.x data.
+#[derive(Debug, Clone, Copy)]
+pub struct Buffers<'s> {
+    source: &'s [u8],
+    buffer_size: usize,
+}
+
+impl<'s> Buffers<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self::with_buffer_size(source, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Read buffers of `buffer_size` bytes.
+    ///
+    /// A size too small to hold a buffer header gives a single error. Otherwise, since the
+    /// buffers are all the same size, a bad buffer doesn't stop the ones after it from being read.
+    pub fn with_buffer_size(source: &'s [u8], buffer_size: usize) -> Self {
+        Self {
+            source,
+            buffer_size,
+        }
+    }
+
+    /// Convert all of the buffers to 11.x ring items.
+    pub fn to_ring_items(self) -> Result<Vec<u8>, ParseError> {
+        let mut out = Vec::new();
+        for buffer in self {
+            buffer?.push_ring_items(&mut out)?;
+        }
+        Ok(out)
+    }
+}
+
+impl<'s> Iterator for Buffers<'s> {
+    type Item = Result<Buffer<'s>, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.source.is_empty() {
+            None
+        } else if self.buffer_size < HEADER_SIZE {
+            // Every buffer would be empty, so iteration would never advance
+            self.source = &[];
+            Some(Err(ParseError::OutOfBounds {
+                offset: 0,
+                len: HEADER_SIZE,
+            }))
+        } else if self.source.len() < self.buffer_size {
+            let available = self.source.len();
+            self.source = &[];
+            Some(Err(ParseError::TruncatedItem {
+                size: self.buffer_size,
+                available,
+            }))
+        } else {
+            let (buffer, rest) = self.source.split_at(self.buffer_size);
+            self.source = rest;
+            Some(Buffer::new(buffer))
+        }
+    }
+}
+
+/// A single 8.x buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Buffer<'s> {
+    source: &'s [u8],
+}
+
+impl<'s> Buffer<'s> {
+    pub fn new(source: &'s [u8]) -> Result<Self, ParseError> {
+        let words = u16::try_from_slice(source, 0)? as usize;
+        if words * 2 < HEADER_SIZE {
+            return Err(ParseError::OutOfBounds {
+                offset: 0,
+                len: HEADER_SIZE,
+            });
+        }
+        let source = source.get(..words * 2).ok_or(ParseError::TruncatedItem {
+            size: words * 2,
+            available: source.len(),
+        })?;
+        Ok(Self { source })
+    }
+
+    /// The used part of the buffer, including the header.
+    pub fn bytes(&self) -> &[u8] {
+        self.source
+    }
+
+    // The header accessors can't fail, since the size is checked in `new`
+
+    /// The number of 16-bit words used in the buffer, including the header.
+    pub fn word_count(&self) -> u16 {
+        u16::try_from_slice(self.source, 0).unwrap()
+    }
+
+    pub fn buffer_type(&self) -> u16 {
+        u16::try_from_slice(self.source, 2).unwrap()
+    }
+
+    pub fn checksum(&self) -> u16 {
+        u16::try_from_slice(self.source, 4).unwrap()
+    }
+
+    pub fn run_number(&self) -> u16 {
+        u16::try_from_slice(self.source, 6).unwrap()
+    }
+
+    pub fn sequence(&self) -> u32 {
+        u32::try_from_slice(self.source, 8).unwrap()
+    }
+
+    /// The number of entities (events, scalers, strings, ...) in the buffer.
+    pub fn entity_count(&self) -> u16 {
+        u16::try_from_slice(self.source, 12).unwrap()
+    }
+
+    pub fn lam_count(&self) -> u16 {
+        u16::try_from_slice(self.source, 14).unwrap()
+    }
+
+    pub fn cpu(&self) -> u16 {
+        u16::try_from_slice(self.source, 16).unwrap()
+    }
+
+    pub fn bit_register_count(&self) -> u16 {
+        u16::try_from_slice(self.source, 18).unwrap()
+    }
+
+    /// The revision of the buffer format.
+    pub fn revision(&self) -> u16 {
+        u16::try_from_slice(self.source, 20).unwrap()
+    }
+
+    /// The bytes after the header.
+    pub fn body(&self) -> &'s [u8] {
+        &self.source[HEADER_SIZE..]
+    }
+
+    /// Convert the entities in the buffer to 11.x ring items.
+    ///
+    /// Buffers of types that have no ring item counterpart produce no items.
+    pub fn to_ring_items(&self) -> Result<Vec<u8>, ParseError> {
+        let mut out = Vec::new();
+        self.push_ring_items(&mut out)?;
+        Ok(out)
+    }
+
+    fn push_ring_items(&self, out: &mut Vec<u8>) -> Result<(), ParseError> {
+        match self.buffer_type() {
+            1 => self.push_physics_events(out),
+            2 => self.push_scalers(out, true),
+            3 => self.push_scalers(out, false),
+            4 | 5 => self.push_text(out, 11),
+            6 => self.push_text(out, 10),
+            // The state change buffer types are the ring item types plus 10
+            x @ 11..=14 => self.push_state_change(out, x as u32 - 10),
+            _ => Ok(()),
+        }
+    }
+
+    fn push_physics_events(&self, out: &mut Vec<u8>) -> Result<(), ParseError> {
+        let body = self.body();
+        let mut offset = 0;
+        for _ in 0..self.entity_count() {
+            // The event size is in words, and includes the size itself
+            let size = u16::try_from_slice(body, offset)? as usize * 2;
+            if size == 0 {
+                return Err(ParseError::ZeroSize);
+            }
+            push_item(out, 30, &[bytes_at(body, offset, size)?]);
+            offset += size;
+        }
+        Ok(())
+    }
+
+    fn push_scalers(&self, out: &mut Vec<u8>, is_incremental: bool) -> Result<(), ParseError> {
+        let body = self.body();
+        let end = u32::try_from_slice(body, 0)?;
+        let start = u32::try_from_slice(body, 10)?;
+        let count = self.entity_count() as usize;
+        let scalers = bytes_at(body, 20, count * 4)?;
+        push_item(
+            out,
+            20,
+            &[
+                &start.to_le_bytes(),
+                &end.to_le_bytes(),
+                // There's no absolute time in a scaler buffer
+                &0u32.to_le_bytes(),
+                &1u32.to_le_bytes(),
+                &(count as u32).to_le_bytes(),
+                &(is_incremental as u32).to_le_bytes(),
+                scalers,
+            ],
+        );
+        Ok(())
+    }
+
+    fn push_text(&self, out: &mut Vec<u8>, type_id: u32) -> Result<(), ParseError> {
+        let body = self.body();
+        let mut offset = 0;
+        let mut strings = Vec::new();
+        for _ in 0..self.entity_count() {
+            let rest = bytes_from(body, offset)?;
+            let len = rest
+                .iter()
+                .position(|x| *x == 0)
+                .ok_or(ParseError::OutOfBounds {
+                    offset,
+                    len: rest.len() + 1,
+                })?;
+            strings.extend_from_slice(&rest[..=len]);
+            // Strings are padded to a whole number of words
+            offset += (len + 2) & !1;
+        }
+        push_item(
+            out,
+            type_id,
+            &[
+                &0u32.to_le_bytes(),
+                &0u32.to_le_bytes(),
+                &(self.entity_count() as u32).to_le_bytes(),
+                &1u32.to_le_bytes(),
+                &strings,
+            ],
+        );
+        Ok(())
+    }
+
+    fn push_state_change(&self, out: &mut Vec<u8>, type_id: u32) -> Result<(), ParseError> {
+        let body = self.body();
+        let title = bytes_at(body, 0, 80)?;
+        let time_offset = u32::try_from_slice(body, 80)?;
+        let mut date = [0; 6];
+        for (i, x) in date.iter_mut().enumerate() {
+            *x = u16::try_from_slice(body, 84 + i * 2)?;
+        }
+        push_item(
+            out,
+            type_id,
+            &[
+                &(self.run_number() as u32).to_le_bytes(),
+                &time_offset.to_le_bytes(),
+                &unix_time(date).to_le_bytes(),
+                &1u32.to_le_bytes(),
+                title,
+                // Ring items have room for a terminating NUL after an 80 byte title
+                &[0],
+            ],
+        );
+        Ok(())
+    }
+}
+
+/// Append a ring item with an empty body header and a body made of `parts`.
+fn push_item(out: &mut Vec<u8>, type_id: u32, parts: &[&[u8]]) {
+    let size = 12 + parts.iter().map(|x| x.len()).sum::<usize>();
+    out.extend_from_slice(&(size as u32).to_le_bytes());
+    out.extend_from_slice(&type_id.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    for part in parts {
+        out.extend_from_slice(part);
+    }
+}
+
+/// Convert a `[month, day, year, hour, minute, second]` date to seconds since the Unix epoch.
+///
+/// The year may be either a full year or years since 1900.
+fn unix_time([month, day, year, hour, minute, second]: [u16; 6]) -> u32 {
+    let year = match year as i64 {
+        x if x < 1900 => x + 1900,
+        x => x,
+    };
+    // Days from the epoch, from Howard Hinnant's `days_from_civil`
+    let (month, day) = (month as i64, day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+    seconds.clamp(0, u32::MAX as i64) as u32
+}
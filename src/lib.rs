@@ -1,39 +1,184 @@
 #![allow(dead_code)]
-// TODO: Handle unwraps?
-// TODO: Handle out of bounds?
 
-use bits::TryFromSlice;
+use bits::{bytes_at, bytes_from, TryFromSlice};
+pub use clock::RunClock;
+use clock::{offset_duration, system_time};
+pub use cursor::Cursor;
+pub use error::{ParseError, ReadError};
+pub use fragment::{Fragment, FragmentWalker, Fragments, Leaf, Level};
+pub use owned::OwnedEvent;
+pub use reader::NsclReader;
+pub use resync::{Resync, Skipped};
+use std::time::{Duration, SystemTime};
+pub use writer::RingItemWriter;
 mod bits;
+pub mod caen;
+mod clock;
+mod cursor;
+pub mod ddas;
+pub mod decode;
+mod error;
+mod fragment;
+pub mod legacy;
+pub mod mesytec;
+pub mod monitor;
+mod owned;
+pub mod packet;
+mod reader;
+mod resync;
+pub mod scaler;
+pub mod v1190;
+pub mod writer;
+pub mod xxusb;
+
+/// The number of items that [`FormatVersion::detect`] looks at, which is enough to make it
+/// unlikely that 10.x data looks like body headers by chance.
+const DETECT_ITEMS: usize = 8;
+
+/// The version of the NSCLDAQ data format, which determines the layout of some ring items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FormatVersion {
+    /// 10.x, where ring items have no body header.
+    V10,
+    #[default]
+    V11,
+    V12,
+}
+
+impl FormatVersion {
+    /// The format version with the given major version (as in a [`RingFormat`] item).
+    pub fn from_major(major: u16) -> Option<Self> {
+        match major {
+            10 => Some(Self::V10),
+            11 => Some(Self::V11),
+            12 => Some(Self::V12),
+            _ => None,
+        }
+    }
+
+    /// Guess the format version from the start of `source`.
+    ///
+    /// 11.x and 12.x writers start every file with a [`RingFormat`] item, so that is used if
+    /// there is one. Without it (e.g. in a later segment of a run, or in filtered output), the
+    /// word after the type of each of the first few items is checked as a body header size. The
+    /// data is only taken to be 10.x if that word can't be one, or if full body headers change
+    /// size from item to item, since a 10.x body rarely looks like the same body header over and
+    /// over. Otherwise, it's 12.x if the empty body headers have size 4, and 11.x if not. Use
+    /// [`NsclData::with_version`] if the format is known.
+    pub fn detect(source: &[u8]) -> Self {
+        let mut data = NsclData::with_version(source, Self::V11);
+        match data.next() {
+            Some(Ok(event)) if event.type_id() == 12 => return data.version,
+            Some(Ok(_)) => {}
+            // There's nothing to go on
+            _ => return Self::default(),
+        }
+
+        let mut empty = None;
+        let mut full = None;
+        for event in NsclData::with_version(source, Self::V10).take(DETECT_ITEMS) {
+            let event = match event {
+                Ok(event) => event,
+                Err(_) => break,
+            };
+            let size = event.bytes().len();
+            match u32::try_from_slice(event.bytes(), 8) {
+                // Only 12.x writes empty body headers as size 4
+                Ok(x @ (0 | 4)) => {
+                    let version = if x == 0 { Self::V11 } else { Self::V12 };
+                    if empty.is_some_and(|x| x != version) {
+                        return Self::V10;
+                    }
+                    empty = Some(version);
+                }
+                Ok(x) if x >= 20 && x as usize <= size - 8 => {
+                    if full.is_some_and(|y| y != x) {
+                        return Self::V10;
+                    }
+                    full = Some(x);
+                }
+                _ => return Self::V10,
+            }
+        }
+        empty.unwrap_or_default()
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct NsclData<'s> {
     source: &'s [u8],
+    offset: usize,
+    version: FormatVersion,
 }
 
 impl<'s> NsclData<'s> {
+    /// Read data in the format detected by [`FormatVersion::detect`], until a [`RingFormat`]
+    /// item says otherwise.
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::detect(source))
+    }
+
+    /// Read data in the given format, until a [`RingFormat`] item says otherwise.
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self {
+            source,
+            offset: 0,
+            version,
+        }
+    }
+
+    /// The number of bytes from the start of the data to the next event.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The format version that the next event will be read with.
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Skip over bad regions of the data instead of stopping at the first error.
+    ///
+    /// `on_skip` is called with every region that is skipped. When skipping forward, only items
+    /// with known types are taken to be the next event; see [`Resync::known_types_only`] to also
+    /// require that of the item where the previous one ended.
+    pub fn resync<F: FnMut(Skipped)>(self, on_skip: F) -> Resync<'s, F> {
+        Resync::new(self, on_skip)
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.source = &self.source[n..];
+        self.offset += n;
+    }
+
+    /// Move past `event`, switching formats if it is a [`RingFormat`] item.
+    fn accept(&mut self, event: &Event) {
+        self.advance(event.size() as usize);
+        if let Ok(RingItem::RingFormat(ri)) = event.ring_item() {
+            if let Some(version) = ri.major().ok().and_then(FormatVersion::from_major) {
+                self.version = version;
+            }
+        }
     }
 }
 
 impl<'s> Iterator for NsclData<'s> {
-    type Item = Event<'s>;
+    type Item = Result<Event<'s>, ParseError>;
     fn next(&mut self) -> Option<Self::Item> {
         if self.source.is_empty() {
             None
         } else {
-            let event = Event::new(self.source);
-
-            // Advance pointer
-            // If event size is 0, something's wrong
-            let size = event.size() as usize;
-            if size == 0 {
-                panic!("event size is 0")
-            } else {
-                self.source = &self.source[size..];
+            match Event::with_version(self.source, self.version) {
+                Ok(event) => {
+                    self.accept(&event);
+                    Some(Ok(event))
+                }
+                Err(e) => {
+                    // There's no way to know where the next event starts, so stop here
+                    self.source = &[];
+                    Some(Err(e))
+                }
             }
-
-            Some(event)
         }
     }
 }
@@ -41,14 +186,30 @@ impl<'s> Iterator for NsclData<'s> {
 #[derive(Debug, Clone, Copy)]
 pub struct Event<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> Event<'s> {
-    pub fn new(source: &'s [u8]) -> Self {
-        let size = u32::try_from_slice(source, 0).unwrap() as usize;
-        Self {
-            source: &source[..size],
+    pub fn new(source: &'s [u8]) -> Result<Self, ParseError> {
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Result<Self, ParseError> {
+        // Not even the size is there, so this must be the tail end of a cut-off item
+        let size = u32::try_from_slice(source, 0).map_err(|_| ParseError::TruncatedItem {
+            size: 4,
+            available: source.len(),
+        })? as usize;
+        if size == 0 {
+            return Err(ParseError::ZeroSize);
         }
+        let source = source.get(..size).ok_or(ParseError::TruncatedItem {
+            size,
+            available: source.len(),
+        })?;
+        // Make sure the type is there, so that it can be read infallibly
+        u32::try_from_slice(source, 4)?;
+        Ok(Self { source, version })
     }
 
     pub fn bytes(&self) -> &[u8] {
@@ -56,80 +217,106 @@ impl<'s> Event<'s> {
     }
 
     pub fn size(&self) -> u32 {
+        // Checked in `new`
         u32::try_from_slice(self.source, 0).unwrap()
     }
 
     pub fn type_id(&self) -> u32 {
+        // Checked in `new`
         u32::try_from_slice(self.source, 4).unwrap()
     }
 
-    pub fn body_header(&self) -> BodyHeader {
-        BodyHeader::new(&self.source[8..])
+    pub fn version(&self) -> FormatVersion {
+        self.version
     }
 
-    pub fn ring_item(&self) -> RingItem<'s> {
-        let offset = match self.body_header().size() {
-            0 => 12 as usize,
-            x => x as usize + 8,
-        };
-        RingItem::new(&self.source[offset..], self.type_id())
+    pub fn body_header(&self) -> Result<BodyHeader<'s>, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(BodyHeader::Absent),
+            _ => BodyHeader::new(bytes_from(self.source, 8)?),
+        }
+    }
+
+    pub fn ring_item(&self) -> Result<RingItem<'s>, ParseError> {
+        let offset = 8 + self.body_header()?.bytes().len();
+        Ok(RingItem::with_version(
+            bytes_from(self.source, offset)?,
+            self.type_id(),
+            self.version,
+        ))
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum BodyHeader<'s> {
+    /// An empty body header, which has size 0 in 11.x and size 4 in 12.x.
     BodyHeader0 { source: &'s [u8] },
+    /// A full body header, which is 20 bytes plus any extension.
     BodyHeader20 { source: &'s [u8] },
+    /// No body header at all, as in 10.x.
+    Absent,
 }
 
 impl<'s> BodyHeader<'s> {
-    pub fn new(source: &'s [u8]) -> Self {
-        let size = u32::try_from_slice(source, 0).unwrap();
+    pub fn new(source: &'s [u8]) -> Result<Self, ParseError> {
+        let size = u32::try_from_slice(source, 0)?;
         match size {
-            0 => Self::BodyHeader0 {
-                source: &source[..4],
-            },
-            20 => Self::BodyHeader20 {
-                source: &source[..20],
-            },
-            _ => panic!("bad BodyHeader size"),
+            0 | 4 => Ok(Self::BodyHeader0 {
+                source: bytes_at(source, 0, 4)?,
+            }),
+            x if x >= 20 => Ok(Self::BodyHeader20 {
+                source: bytes_at(source, 0, x as usize)?,
+            }),
+            _ => Err(ParseError::BadBodyHeaderSize(size)),
         }
     }
 
     pub fn bytes(&self) -> &[u8] {
         match self {
             Self::BodyHeader0 { source } | Self::BodyHeader20 { source } => source,
+            Self::Absent => &[],
         }
     }
 
+    // The accessors below can't fail, since the sizes are checked in `new`
+
     pub fn size(&self) -> u32 {
         match self {
             Self::BodyHeader0 { source } | Self::BodyHeader20 { source } => {
                 u32::try_from_slice(source, 0).unwrap()
             }
+            Self::Absent => 0,
         }
     }
 
     pub fn timestamp(&self) -> Option<u64> {
         match self {
-            Self::BodyHeader0 { .. } => None,
+            Self::BodyHeader0 { .. } | Self::Absent => None,
             Self::BodyHeader20 { source } => Some(u64::try_from_slice(source, 4).unwrap()),
         }
     }
 
     pub fn source_id(&self) -> Option<u32> {
         match self {
-            Self::BodyHeader0 { .. } => None,
+            Self::BodyHeader0 { .. } | Self::Absent => None,
             Self::BodyHeader20 { source } => Some(u32::try_from_slice(source, 12).unwrap()),
         }
     }
 
     pub fn barrier_type(&self) -> Option<u32> {
         match self {
-            Self::BodyHeader0 { .. } => None,
+            Self::BodyHeader0 { .. } | Self::Absent => None,
             Self::BodyHeader20 { source } => Some(u32::try_from_slice(source, 16).unwrap()),
         }
     }
+
+    /// The bytes following the barrier type in an extended body header.
+    pub fn extension(&self) -> Option<&[u8]> {
+        match self {
+            Self::BodyHeader0 { .. } | Self::Absent => None,
+            Self::BodyHeader20 { source } => Some(&source[20..]),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -149,47 +336,80 @@ pub enum RingItem<'s> {
     EvbUnknownPayload(EvbUnknownPayload<'s>),
     EvbGlomInfo(EvbGlomInfo<'s>),
     UserItem(UserItem<'s>),
+    /// A ring item with a type that isn't understood (e.g. from a newer NSCLDAQ or a local
+    /// experiment), but which isn't in the user item range.
+    Unknown {
+        type_id: u32,
+        source: &'s [u8],
+    },
 }
 
 impl<'s> RingItem<'s> {
     pub fn new(source: &'s [u8], type_id: u32) -> Self {
+        Self::with_version(source, type_id, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], type_id: u32, version: FormatVersion) -> Self {
         match type_id {
-            1 => Self::BeginRun(StateChange::new(source)),
-            2 => Self::EndRun(StateChange::new(source)),
-            3 => Self::PauseRun(StateChange::new(source)),
-            4 => Self::ResumeRun(StateChange::new(source)),
-            5 => Self::AbnormalEndRun(StateChange::new(source)),
-            10 => Self::PacketTypes(Text::new(source)),
-            11 => Self::MonitoredVariables(Text::new(source)),
+            1 => Self::BeginRun(StateChange::with_version(source, version)),
+            2 => Self::EndRun(StateChange::with_version(source, version)),
+            3 => Self::PauseRun(StateChange::with_version(source, version)),
+            4 => Self::ResumeRun(StateChange::with_version(source, version)),
+            5 => Self::AbnormalEndRun(StateChange::with_version(source, version)),
+            10 => Self::PacketTypes(Text::with_version(source, version)),
+            11 => Self::MonitoredVariables(Text::with_version(source, version)),
             12 => Self::RingFormat(RingFormat::new(source)),
-            20 => Self::PeriodicScalers(PeriodicScalers::new(source)),
-            30 => Self::PhysicsEvent(PhysicsEvent::new(source)),
-            31 => Self::PhysicsEventCount(PhysicsEventCount::new(source)),
-            40 => Self::EvbFragment(EvbFragment::new(source)),
+            20 => Self::PeriodicScalers(PeriodicScalers::with_version(source, version)),
+            30 => Self::PhysicsEvent(PhysicsEvent::with_version(source, version)),
+            31 => Self::PhysicsEventCount(PhysicsEventCount::with_version(source, version)),
+            40 => Self::EvbFragment(EvbFragment::with_version(source, version)),
             41 => Self::EvbUnknownPayload(EvbUnknownPayload::new(source)),
             42 => Self::EvbGlomInfo(EvbGlomInfo::new(source)),
             x if x > 32768 => Self::UserItem(UserItem::new(source)),
-            _ => panic!("unknown ring item type"),
+            type_id => Self::Unknown { type_id, source },
         }
     }
 
+    /// Whether `type_id` is one of the ring item types that has its own variant.
+    pub fn is_known_type(type_id: u32) -> bool {
+        matches!(type_id, 1..=5 | 10..=12 | 20 | 30 | 31 | 40..=42) || type_id > 32768
+    }
+
     pub fn bytes(&self) -> &'s [u8] {
         match self {
-            Self::BeginRun(StateChange { source })
-            | Self::EndRun(StateChange { source })
-            | Self::PauseRun(StateChange { source })
-            | Self::ResumeRun(StateChange { source })
-            | Self::AbnormalEndRun(StateChange { source })
-            | Self::PacketTypes(Text { source })
-            | Self::MonitoredVariables(Text { source })
+            Self::BeginRun(StateChange { source, .. })
+            | Self::EndRun(StateChange { source, .. })
+            | Self::PauseRun(StateChange { source, .. })
+            | Self::ResumeRun(StateChange { source, .. })
+            | Self::AbnormalEndRun(StateChange { source, .. })
+            | Self::PacketTypes(Text { source, .. })
+            | Self::MonitoredVariables(Text { source, .. })
             | Self::RingFormat(RingFormat { source })
-            | Self::PeriodicScalers(PeriodicScalers { source })
-            | Self::PhysicsEvent(PhysicsEvent { source })
-            | Self::PhysicsEventCount(PhysicsEventCount { source })
-            | Self::EvbFragment(EvbFragment { source })
+            | Self::PeriodicScalers(PeriodicScalers { source, .. })
+            | Self::PhysicsEvent(PhysicsEvent { source, .. })
+            | Self::PhysicsEventCount(PhysicsEventCount { source, .. })
+            | Self::EvbFragment(EvbFragment { source, .. })
             | Self::EvbUnknownPayload(EvbUnknownPayload { source })
             | Self::EvbGlomInfo(EvbGlomInfo { source })
-            | Self::UserItem(UserItem { source }) => source,
+            | Self::UserItem(UserItem { source })
+            | Self::Unknown { source, .. } => source,
+        }
+    }
+
+    /// The time since the start of the run, for items that have a time offset.
+    ///
+    /// For scalers, this is the end of the interval.
+    pub fn elapsed(&self) -> Result<Option<Duration>, ParseError> {
+        match self {
+            Self::BeginRun(ri)
+            | Self::EndRun(ri)
+            | Self::PauseRun(ri)
+            | Self::ResumeRun(ri)
+            | Self::AbnormalEndRun(ri) => ri.elapsed().map(Some),
+            Self::PacketTypes(ri) | Self::MonitoredVariables(ri) => ri.elapsed().map(Some),
+            Self::PeriodicScalers(ri) => ri.interval_end().map(Some),
+            Self::PhysicsEventCount(ri) => ri.elapsed().map(Some),
+            _ => Ok(None),
         }
     }
 
@@ -302,85 +522,150 @@ impl<'s> RingItem<'s> {
 #[derive(Debug, Clone, Copy)]
 pub struct StateChange<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> StateChange<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
 
-    pub fn run_number(&self) -> u32 {
-        u32::try_from_slice(self.source, 0).unwrap()
+    pub fn run_number(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 0)
     }
 
-    pub fn time_offset(&self) -> u32 {
-        u32::try_from_slice(self.source, 4).unwrap()
+    pub fn time_offset(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 4)
+    }
+
+    pub fn timestamp(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 8)
+    }
+
+    /// The divisor for `time_offset` (10.x has no divisor, so it is always 1).
+    pub fn offset_divisor(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(1),
+            _ => u32::try_from_slice(self.source, 12),
+        }
+    }
+
+    /// The time since the start of the run, treating an `offset_divisor` of 0 as 1.
+    pub fn elapsed(&self) -> Result<Duration, ParseError> {
+        Ok(offset_duration(self.time_offset()?, self.offset_divisor()?))
     }
 
-    pub fn timestamp(&self) -> u32 {
-        u32::try_from_slice(self.source, 8).unwrap()
+    /// The `timestamp` as a calendar time.
+    pub fn system_time(&self) -> Result<SystemTime, ParseError> {
+        Ok(system_time(self.timestamp()?))
     }
 
-    pub fn offset_divisor(&self) -> u32 {
-        u32::try_from_slice(self.source, 12).unwrap()
+    /// The source id of the item before it went through the event builder (12.x only).
+    pub fn original_sid(&self) -> Result<Option<u32>, ParseError> {
+        match self.version {
+            FormatVersion::V10 | FormatVersion::V11 => Ok(None),
+            FormatVersion::V12 => u32::try_from_slice(self.source, 16).map(Some),
+        }
     }
 
-    pub fn title_bytes(&self) -> &[u8] {
+    pub fn title_bytes(&self) -> Result<&[u8], ParseError> {
         // You could just go to the end of source, since the event should end after the string
-        &self.source[16..][..80]
+        let offset = match self.version {
+            FormatVersion::V10 => 12,
+            FormatVersion::V11 => 16,
+            FormatVersion::V12 => 20,
+        };
+        bytes_at(self.source, offset, 80)
     }
 
-    pub fn title(&self) -> &str {
+    pub fn title(&self) -> Result<&str, ParseError> {
         // Ignore bytes starting with the first NUL
-        let end = self.source[16..].iter().position(|x| *x == 0).unwrap();
-        std::str::from_utf8(&self.source[16..][..end]).unwrap()
+        let title = self.title_bytes()?;
+        let end = title.iter().position(|x| *x == 0).unwrap_or(title.len());
+        Ok(std::str::from_utf8(&title[..end])?)
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Text<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> Text<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
 
-    pub fn time_offset(&self) -> u32 {
-        u32::try_from_slice(self.source, 0).unwrap()
+    pub fn time_offset(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 0)
     }
 
-    pub fn timestamp(&self) -> u32 {
-        u32::try_from_slice(self.source, 4).unwrap()
+    pub fn timestamp(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 4)
     }
 
-    pub fn string_count(&self) -> u32 {
-        u32::try_from_slice(self.source, 8).unwrap()
+    pub fn string_count(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 8)
     }
 
-    pub fn offset_divisor(&self) -> u32 {
-        u32::try_from_slice(self.source, 12).unwrap()
+    /// The divisor for `time_offset` (10.x has no divisor, so it is always 1).
+    pub fn offset_divisor(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(1),
+            _ => u32::try_from_slice(self.source, 12),
+        }
     }
 
-    pub fn strings_bytes(&self) -> &[u8] {
-        &self.source[16..]
+    /// The time since the start of the run, treating an `offset_divisor` of 0 as 1.
+    pub fn elapsed(&self) -> Result<Duration, ParseError> {
+        Ok(offset_duration(self.time_offset()?, self.offset_divisor()?))
+    }
+
+    /// The `timestamp` as a calendar time.
+    pub fn system_time(&self) -> Result<SystemTime, ParseError> {
+        Ok(system_time(self.timestamp()?))
+    }
+
+    /// The source id of the item before it went through the event builder (12.x only).
+    pub fn original_sid(&self) -> Result<Option<u32>, ParseError> {
+        match self.version {
+            FormatVersion::V10 | FormatVersion::V11 => Ok(None),
+            FormatVersion::V12 => u32::try_from_slice(self.source, 16).map(Some),
+        }
     }
 
-    pub fn strings(&self) -> Vec<&str> {
-        let n = self.string_count();
-        self.source[16..]
+    pub fn strings_bytes(&self) -> Result<&[u8], ParseError> {
+        let offset = match self.version {
+            FormatVersion::V10 => 12,
+            FormatVersion::V11 => 16,
+            FormatVersion::V12 => 20,
+        };
+        bytes_from(self.source, offset)
+    }
+
+    pub fn strings(&self) -> Result<Vec<&str>, ParseError> {
+        let n = self.string_count()?;
+        self.strings_bytes()?
             .split(|x| *x == 0)
             .take(n as usize) // split leaves an empty string at the end
-            .map(|x| std::str::from_utf8(x).unwrap())
+            .map(|x| Ok(std::str::from_utf8(x)?))
             .collect()
     }
 }
@@ -399,60 +684,109 @@ impl<'s> RingFormat<'s> {
         self.source
     }
 
-    pub fn major(&self) -> u16 {
-        u16::try_from_slice(self.source, 0).unwrap()
+    pub fn major(&self) -> Result<u16, ParseError> {
+        u16::try_from_slice(self.source, 0)
     }
 
-    pub fn minor(&self) -> u16 {
-        u16::try_from_slice(self.source, 2).unwrap()
+    pub fn minor(&self) -> Result<u16, ParseError> {
+        u16::try_from_slice(self.source, 2)
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct PeriodicScalers<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> PeriodicScalers<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
 
-    pub fn interval_start_offset(&self) -> u32 {
-        u32::try_from_slice(self.source, 0).unwrap()
+    pub fn interval_start_offset(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 0)
     }
 
-    pub fn interval_end_offset(&self) -> u32 {
-        u32::try_from_slice(self.source, 4).unwrap()
+    pub fn interval_end_offset(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 4)
     }
 
-    pub fn timestamp(&self) -> u32 {
-        u32::try_from_slice(self.source, 8).unwrap()
+    pub fn timestamp(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 8)
     }
 
-    pub fn interval_divisor(&self) -> u32 {
-        u32::try_from_slice(self.source, 12).unwrap()
+    /// The divisor for the interval offsets (10.x has no divisor, so it is always 1).
+    pub fn interval_divisor(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(1),
+            _ => u32::try_from_slice(self.source, 12),
+        }
+    }
+
+    pub fn scaler_count(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => u32::try_from_slice(self.source, 12),
+            _ => u32::try_from_slice(self.source, 16),
+        }
+    }
+
+    /// Whether the scalers are cleared after each read (10.x scalers always are).
+    pub fn is_incremental(&self) -> Result<bool, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(true),
+            _ => Ok(u32::try_from_slice(self.source, 20)? != 0),
+        }
+    }
+
+    /// The start of the interval since the start of the run, treating an `interval_divisor` of 0
+    /// as 1.
+    pub fn interval_start(&self) -> Result<Duration, ParseError> {
+        Ok(offset_duration(
+            self.interval_start_offset()?,
+            self.interval_divisor()?,
+        ))
+    }
+
+    /// The end of the interval since the start of the run, treating an `interval_divisor` of 0 as
+    /// 1.
+    pub fn interval_end(&self) -> Result<Duration, ParseError> {
+        Ok(offset_duration(
+            self.interval_end_offset()?,
+            self.interval_divisor()?,
+        ))
     }
 
-    pub fn scaler_count(&self) -> u32 {
-        u32::try_from_slice(self.source, 16).unwrap()
+    /// The `timestamp` as a calendar time.
+    pub fn system_time(&self) -> Result<SystemTime, ParseError> {
+        Ok(system_time(self.timestamp()?))
     }
 
-    pub fn is_incremental(&self) -> bool {
-        match u32::try_from_slice(self.source, 20).unwrap() {
-            0 => false,
-            _ => true,
+    /// The source id of the item before it went through the event builder (12.x only).
+    pub fn original_sid(&self) -> Result<Option<u32>, ParseError> {
+        match self.version {
+            FormatVersion::V10 | FormatVersion::V11 => Ok(None),
+            FormatVersion::V12 => u32::try_from_slice(self.source, 24).map(Some),
         }
     }
 
-    pub fn scalers(&self) -> Vec<u32> {
-        (0..self.scaler_count() as usize)
+    pub fn scalers(&self) -> Result<Vec<u32>, ParseError> {
+        let offset = match self.version {
+            FormatVersion::V10 => 16,
+            FormatVersion::V11 => 24,
+            FormatVersion::V12 => 28,
+        };
+        (0..self.scaler_count()? as usize)
             // Each scaler is 4 bytes long
-            .map(|i| u32::try_from_slice(self.source, 24 + i * 4).unwrap())
+            .map(|i| u32::try_from_slice(self.source, offset + i * 4))
             .collect()
     }
 }
@@ -460,62 +794,153 @@ impl<'s> PeriodicScalers<'s> {
 #[derive(Debug, Clone, Copy)]
 pub struct PhysicsEvent<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> PhysicsEvent<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
+
+    /// The size word at the start of the body.
+    ///
+    /// This usually includes itself, but the units depend on what produced the data: 16-bit
+    /// words for most readout programs, and bytes for the event builder.
+    pub fn body_size(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 0)
+    }
+
+    /// The body as 16-bit words, ignoring any odd byte at the end.
+    pub fn words16(&self) -> impl Iterator<Item = u16> + 's {
+        self.source
+            .chunks_exact(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+    }
+
+    /// The body as 32-bit words, ignoring any partial word at the end.
+    pub fn words32(&self) -> impl Iterator<Item = u32> + 's {
+        self.source
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+    }
+
+    /// A cursor at the start of the body.
+    pub fn cursor(&self) -> Cursor<'s> {
+        Cursor::new(self.source)
+    }
+
+    /// The fragments in an event built by the event builder.
+    pub fn fragments(&self) -> Result<Fragments<'s>, ParseError> {
+        // The body starts with its size in bytes, including the size itself
+        let size = u32::try_from_slice(self.source, 0)? as usize;
+        if size == 0 {
+            return Err(ParseError::ZeroSize);
+        }
+        let source = self.source.get(4..size).ok_or(ParseError::TruncatedItem {
+            size,
+            available: self.source.len(),
+        })?;
+        Ok(Fragments::with_version(source, self.version))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct PhysicsEventCount<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> PhysicsEventCount<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
 
-    pub fn time_offset(&self) -> u32 {
-        u32::try_from_slice(self.source, 0).unwrap()
+    pub fn time_offset(&self) -> Result<u32, ParseError> {
+        u32::try_from_slice(self.source, 0)
     }
 
-    pub fn offset_divisor(&self) -> u32 {
-        u32::try_from_slice(self.source, 4).unwrap()
+    /// The divisor for `time_offset` (10.x has no divisor, so it is always 1).
+    pub fn offset_divisor(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => Ok(1),
+            _ => u32::try_from_slice(self.source, 4),
+        }
+    }
+
+    /// The time since the start of the run, treating an `offset_divisor` of 0 as 1.
+    pub fn elapsed(&self) -> Result<Duration, ParseError> {
+        Ok(offset_duration(self.time_offset()?, self.offset_divisor()?))
+    }
+
+    /// The `timestamp` as a calendar time.
+    pub fn system_time(&self) -> Result<SystemTime, ParseError> {
+        Ok(system_time(self.timestamp()?))
+    }
+
+    /// The source id of the item before it went through the event builder (12.x only).
+    pub fn original_sid(&self) -> Result<Option<u32>, ParseError> {
+        match self.version {
+            FormatVersion::V10 | FormatVersion::V11 => Ok(None),
+            FormatVersion::V12 => u32::try_from_slice(self.source, 8).map(Some),
+        }
     }
 
-    pub fn timestamp(&self) -> u32 {
-        u32::try_from_slice(self.source, 8).unwrap()
+    pub fn timestamp(&self) -> Result<u32, ParseError> {
+        match self.version {
+            FormatVersion::V10 => u32::try_from_slice(self.source, 4),
+            FormatVersion::V11 => u32::try_from_slice(self.source, 8),
+            FormatVersion::V12 => u32::try_from_slice(self.source, 12),
+        }
     }
 
-    pub fn event_count(&self) -> u64 {
-        u64::try_from_slice(self.source, 12).unwrap()
+    pub fn event_count(&self) -> Result<u64, ParseError> {
+        match self.version {
+            FormatVersion::V10 => u64::try_from_slice(self.source, 8),
+            FormatVersion::V11 => u64::try_from_slice(self.source, 12),
+            FormatVersion::V12 => u64::try_from_slice(self.source, 16),
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct EvbFragment<'s> {
     source: &'s [u8],
+    version: FormatVersion,
 }
 
 impl<'s> EvbFragment<'s> {
     pub fn new(source: &'s [u8]) -> Self {
-        Self { source }
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(source: &'s [u8], version: FormatVersion) -> Self {
+        Self { source, version }
     }
 
     pub fn bytes(&self) -> &[u8] {
         self.source
     }
+
+    /// The fragment's payload, which is a complete ring item.
+    pub fn event(&self) -> Result<Event<'s>, ParseError> {
+        Event::with_version(self.source, self.version)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -547,19 +972,16 @@ impl<'s> EvbGlomInfo<'s> {
         self.source
     }
 
-    pub fn coincident_ticks(&self) -> u64 {
-        u64::try_from_slice(self.source, 0).unwrap()
+    pub fn coincident_ticks(&self) -> Result<u64, ParseError> {
+        u64::try_from_slice(self.source, 0)
     }
 
-    pub fn is_building(&self) -> bool {
-        match u16::try_from_slice(self.source, 8).unwrap() {
-            0 => false,
-            _ => true,
-        }
+    pub fn is_building(&self) -> Result<bool, ParseError> {
+        Ok(u16::try_from_slice(self.source, 8)? != 0)
     }
 
-    pub fn timestamp_policy(&self) -> u16 {
-        u16::try_from_slice(self.source, 10).unwrap()
+    pub fn timestamp_policy(&self) -> Result<u16, ParseError> {
+        u16::try_from_slice(self.source, 10)
     }
 }
 
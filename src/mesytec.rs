@@ -0,0 +1,221 @@
+//! Decoding of Mesytec MADC-32, MQDC-32, and MTDC-32 data.
+//!
+//! Each module's data is a block of 32-bit words: a header, data words for the channels that
+//! converted (and possibly an extended timestamp word), and an end of event word. The header gives
+//! the number of words that follow it, including the end of event word.
+
+use crate::{error, Cursor, ParseError};
+use std::fmt;
+
+/// An error encountered while decoding Mesytec data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// A word that isn't allowed where it is, e.g. a data word before a header.
+    UnexpectedWord(u32),
+    /// The data ended before the end of event word.
+    MissingEndOfEvent,
+    /// The number of words doesn't match the count in the header.
+    CountMismatch {
+        header: u32,
+        words: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::UnexpectedWord(x) => write!(f, "unexpected word {:#010x}", x),
+            Self::MissingEndOfEvent => write!(f, "missing end of event"),
+            Self::CountMismatch { header, words } => write!(
+                f,
+                "header count is {}, but there are {} words",
+                header, words
+            ),
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// The kind of module, which determines how data words are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Module {
+    Madc32,
+    Mqdc32,
+    Mtdc32,
+}
+
+/// A single channel's conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datum {
+    pub channel: u32,
+    pub value: u32,
+    /// Whether the conversion was out of range (always false for the MTDC-32).
+    pub out_of_range: bool,
+    /// Whether this is one of the MTDC-32 trigger inputs rather than a regular channel.
+    pub trigger: bool,
+}
+
+/// The data from one module for one event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub module_id: u32,
+    /// The resolution (or output format) setting from the header.
+    pub resolution: u32,
+    /// The 30-bit event counter or timestamp, depending on how the module is set up.
+    pub counter: u32,
+    /// The high 16 bits of the timestamp, if extended timestamps are enabled.
+    pub extended_timestamp: Option<u16>,
+    pub data: Vec<Datum>,
+}
+
+impl Block {
+    /// The counter, extended by the extended timestamp if there is one.
+    pub fn timestamp(&self) -> u64 {
+        let high = self.extended_timestamp.unwrap_or(0) as u64;
+        (high << 30) | self.counter as u64
+    }
+
+    /// The conversion for `channel`, if it was read out.
+    pub fn channel(&self, channel: u32) -> Option<&Datum> {
+        self.data
+            .iter()
+            .find(|x| !x.trigger && x.channel == channel)
+    }
+}
+
+/// An iterator over the blocks in some data, skipping any fill words between blocks.
+#[derive(Debug, Clone, Copy)]
+pub struct Blocks<'s> {
+    cursor: Cursor<'s>,
+    module: Module,
+    scan: bool,
+}
+
+impl<'s> Blocks<'s> {
+    /// Read blocks that directly follow each other (apart from fill words).
+    pub fn new(source: &'s [u8], module: Module) -> Self {
+        Self {
+            cursor: Cursor::new(source),
+            module,
+            scan: false,
+        }
+    }
+
+    /// Search for blocks among other data, skipping any words that don't start a valid block.
+    pub fn scan(source: &'s [u8], module: Module) -> Self {
+        Self {
+            scan: true,
+            ..Self::new(source, module)
+        }
+    }
+
+    /// The bytes after the blocks that have been read so far.
+    pub fn rest(&self) -> &'s [u8] {
+        self.cursor.rest()
+    }
+
+    fn read_block(&self, cursor: &mut Cursor, header: u32) -> Result<Block, Error> {
+        let module_id = (header >> 16) & 0xff;
+        let resolution = (header >> 12) & 0x7;
+        let count = header & 0xfff;
+        let mut words = 0;
+        let mut extended_timestamp = None;
+        let mut data = Vec::new();
+        loop {
+            let word = cursor.read_u32().map_err(|_| Error::MissingEndOfEvent)?;
+            words += 1;
+            if is_end_of_event(word) {
+                if words != count {
+                    return Err(Error::CountMismatch {
+                        header: count,
+                        words,
+                    });
+                }
+                return Ok(Block {
+                    module_id,
+                    resolution,
+                    counter: word & 0x3fff_ffff,
+                    extended_timestamp,
+                    data,
+                });
+            } else if word & 0xffff_0000 == 0x0480_0000 {
+                extended_timestamp = Some(word as u16);
+            } else if word & 0xffe0_0000 == 0x0400_0000
+                || (self.module == Module::Mtdc32 && word & 0xffe0_0000 == 0x0420_0000)
+            {
+                data.push(self.datum(word));
+            } else if word != 0 {
+                return Err(Error::UnexpectedWord(word));
+            }
+        }
+    }
+
+    fn datum(&self, word: u32) -> Datum {
+        let channel = (word >> 16) & 0x1f;
+        match self.module {
+            Module::Madc32 => Datum {
+                channel,
+                value: word & 0x1fff,
+                out_of_range: word & (1 << 14) != 0,
+                trigger: false,
+            },
+            Module::Mqdc32 => Datum {
+                channel,
+                value: word & 0xfff,
+                out_of_range: word & (1 << 15) != 0,
+                trigger: false,
+            },
+            Module::Mtdc32 => Datum {
+                channel,
+                value: word & 0xffff,
+                out_of_range: false,
+                trigger: word & (1 << 21) != 0,
+            },
+        }
+    }
+}
+
+impl<'s> Iterator for Blocks<'s> {
+    type Item = Result<Block, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.cursor.is_empty() {
+            let result = match self.cursor.read_u32() {
+                Ok(word) if is_header(word) => {
+                    let mut cursor = self.cursor;
+                    let result = self.read_block(&mut cursor, word);
+                    if result.is_ok() || !self.scan {
+                        self.cursor = cursor;
+                    }
+                    result
+                }
+                // Fill words, and the 0xffffffff that ends a VME block transfer
+                Ok(0) | Ok(0xffff_ffff) => continue,
+                Ok(word) => Err(Error::UnexpectedWord(word)),
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Err(_) if self.scan => continue,
+                Err(_) => {
+                    // Without scanning for the next header, the rest of a bad block would give an
+                    // error for every word
+                    self.cursor = Cursor::new(&[]);
+                }
+                Ok(_) => {}
+            }
+            return Some(result);
+        }
+        None
+    }
+}
+
+fn is_header(word: u32) -> bool {
+    word & 0xff00_0000 == 0x4000_0000
+}
+
+fn is_end_of_event(word: u32) -> bool {
+    word >> 30 == 3
+}
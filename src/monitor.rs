@@ -0,0 +1,404 @@
+//! Parsing of the Tcl `set name value` commands in `MonitoredVariables` items.
+//!
+//! Tcl values don't have types, so each value is classified by its contents: anything that parses
+//! as a number is a number, a braced or bare value with several words is a list, and anything else
+//! is a string.
+
+use crate::{clock::offset_duration, error, Event, ParseError, Text};
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+/// An error encountered while parsing monitored variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// A string isn't a `set name value` command.
+    BadCommand(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::BadCommand(x) => write!(f, "bad command {:?}", x),
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// The name of a variable, or of an element of an array variable (`name(index)`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Name {
+    pub name: String,
+    pub index: Option<String>,
+}
+
+impl Name {
+    pub fn parse(s: &str) -> Self {
+        match s.strip_suffix(')').and_then(|x| x.split_once('(')) {
+            Some((name, index)) => Self {
+                name: name.to_string(),
+                index: Some(index.to_string()),
+            },
+            None => Self {
+                name: s.to_string(),
+                index: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.index {
+            Some(index) => write!(f, "{}({})", self.name, index),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[String]> {
+        match self {
+            Self::List(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(x) => write!(f, "{}", x),
+            Self::String(x) => write!(f, "{}", x),
+            Self::List(x) => write!(f, "{}", x.join(" ")),
+        }
+    }
+}
+
+/// Parse a `set name value` command.
+pub fn parse_set(s: &str) -> Result<(Name, Value), Error> {
+    let bad = || Error::BadCommand(s.to_string());
+    // Report the whole command, rather than just the unterminated word
+    let word = |x| next_word(x).and_then(Result::ok).ok_or_else(bad);
+    let (command, rest) = word(s)?;
+    if command.text != "set" {
+        return Err(bad());
+    }
+    let (name, rest) = word(rest)?;
+    let (value, rest) = word(rest)?;
+    if !rest.trim().is_empty() {
+        return Err(bad());
+    }
+    let value = if let Some(x) = parse_number(&value.text) {
+        Value::Number(x)
+    } else if value.quoting == Quoting::Quoted {
+        Value::String(value.text)
+    } else {
+        let elements = split_list(&value.text).ok_or_else(bad)?;
+        if elements.len() > 1 {
+            Value::List(elements)
+        } else {
+            Value::String(value.text)
+        }
+    };
+    Ok((Name::parse(&name.text), value))
+}
+
+/// A set of variables and their values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables {
+    values: BTreeMap<Name, Value>,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the commands in a `MonitoredVariables` item.
+    pub fn from_text(text: &Text) -> Result<Self, Error> {
+        let mut variables = Self::new();
+        for s in text.strings()? {
+            let (name, value) = parse_set(s)?;
+            variables.insert(name, value);
+        }
+        Ok(variables)
+    }
+
+    /// Set a variable, returning its old value.
+    pub fn insert(&mut self, name: Name, value: Value) -> Option<Value> {
+        self.values.insert(name, value)
+    }
+
+    /// The value of a variable, or of an array element written as `name(index)`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(&Name::parse(name))
+    }
+
+    /// The value of an element of an array.
+    pub fn element(&self, name: &str, index: &str) -> Option<&Value> {
+        self.values.get(&Name {
+            name: name.to_string(),
+            index: Some(index.to_string()),
+        })
+    }
+
+    /// The indices and values of the elements of an array.
+    pub fn array<'a>(&'a self, name: &'a str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+        self.values.iter().filter_map(move |(k, v)| match &k.index {
+            Some(index) if k.name == name => Some((index.as_str(), v)),
+            _ => None,
+        })
+    }
+
+    /// The variables, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&Name, &Value)> {
+        self.values.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// The latest value of a variable, and when it was set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub value: Value,
+    /// The time offset of the item that set the value.
+    pub time_offset: u32,
+    pub offset_divisor: u32,
+}
+
+impl Sample {
+    /// The time since the start of the run that the value was set.
+    pub fn elapsed(&self) -> Duration {
+        offset_duration(self.time_offset, self.offset_divisor)
+    }
+}
+
+/// A tracker of the latest value of each variable over a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tracker {
+    samples: BTreeMap<Name, Sample>,
+}
+
+impl Tracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the variables set in a `MonitoredVariables` item.
+    pub fn update(&mut self, text: &Text) -> Result<(), Error> {
+        let time_offset = text.time_offset()?;
+        let offset_divisor = text.offset_divisor()?;
+        for s in text.strings()? {
+            let (name, value) = parse_set(s)?;
+            self.samples.insert(
+                name,
+                Sample {
+                    value,
+                    time_offset,
+                    offset_divisor,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Update the variables if `event` is a `MonitoredVariables` item, returning whether it was.
+    pub fn observe(&mut self, event: &Event) -> Result<bool, Error> {
+        match event.ring_item()?.as_monitored_variables() {
+            Some(text) => self.update(&text).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// The latest sample of a variable, or of an array element written as `name(index)`.
+    pub fn get(&self, name: &str) -> Option<&Sample> {
+        self.samples.get(&Name::parse(name))
+    }
+
+    /// The latest sample of an element of an array.
+    pub fn element(&self, name: &str, index: &str) -> Option<&Sample> {
+        self.samples.get(&Name {
+            name: name.to_string(),
+            index: Some(index.to_string()),
+        })
+    }
+
+    /// The latest samples, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&Name, &Sample)> {
+        self.samples.iter()
+    }
+
+    /// The latest values, without their times.
+    pub fn values(&self) -> Variables {
+        Variables {
+            values: self
+                .samples
+                .iter()
+                .map(|(k, v)| (k.clone(), v.value.clone()))
+                .collect(),
+        }
+    }
+
+    /// Forget all of the values, e.g. at the start of a new run.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quoting {
+    Bare,
+    Braced,
+    Quoted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    text: String,
+    quoting: Quoting,
+}
+
+/// Split off the next Tcl word, or return `None` if there isn't one, or `Some(Err)` if it isn't
+/// terminated.
+fn next_word(s: &str) -> Option<Result<(Word, &str), Error>> {
+    let s = s.trim_start();
+    let mut chars = s.char_indices();
+    let (quoting, text, end) = match chars.next()? {
+        (_, '{') => {
+            let mut depth = 1;
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            match end {
+                Some(i) => (Quoting::Braced, s[1..i].to_string(), i + 1),
+                None => return Some(Err(Error::BadCommand(s.to_string()))),
+            }
+        }
+        (_, '"') => {
+            let mut text = String::new();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => text.push(unescape(chars.next().map(|x| x.1))),
+                    '"' => {
+                        end = Some(i + 1);
+                        break;
+                    }
+                    c => text.push(c),
+                }
+            }
+            match end {
+                Some(i) => (Quoting::Quoted, text, i),
+                None => return Some(Err(Error::BadCommand(s.to_string()))),
+            }
+        }
+        (_, first) => {
+            let mut text = String::new();
+            let mut c = Some((0, first));
+            let mut end = s.len();
+            while let Some((i, x)) = c {
+                match x {
+                    x if x.is_whitespace() => {
+                        end = i;
+                        break;
+                    }
+                    '\\' => text.push(unescape(chars.next().map(|x| x.1))),
+                    x => text.push(x),
+                }
+                c = chars.next();
+            }
+            (Quoting::Bare, text, end)
+        }
+    };
+    let rest = &s[end..];
+    // A closing brace or quote must end the word
+    if rest.starts_with(|x: char| !x.is_whitespace()) {
+        return Some(Err(Error::BadCommand(s.to_string())));
+    }
+    Some(Ok((Word { text, quoting }, rest)))
+}
+
+fn unescape(c: Option<char>) -> char {
+    match c {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some(x) => x,
+        None => '\\',
+    }
+}
+
+/// Split a Tcl list into its elements.
+fn split_list(s: &str) -> Option<Vec<String>> {
+    let mut elements = Vec::new();
+    let mut rest = s;
+    while let Some(word) = next_word(rest) {
+        let (word, next) = word.ok()?;
+        elements.push(word.text);
+        rest = next;
+    }
+    Some(elements)
+}
+
+/// Parse a decimal or `0x` hex number, leaving e.g. "inf" and "nan" as strings.
+fn parse_number(s: &str) -> Option<f64> {
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(x) => (-1.0, x),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    match unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok().map(|x| sign * x as f64),
+        None => unsigned
+            .starts_with(|x: char| x.is_ascii_digit() || x == '.')
+            .then(|| unsigned.parse::<f64>().ok().map(|x| sign * x))
+            .flatten(),
+    }
+}
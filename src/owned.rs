@@ -0,0 +1,78 @@
+use crate::{BodyHeader, Event, FormatVersion, ParseError, RingItem};
+use std::sync::Arc;
+
+/// An [`Event`] that owns its bytes, so that it can outlive the data it came from.
+///
+/// The bytes are shared, so cloning is cheap, and it can be sent between threads.
+#[derive(Debug, Clone)]
+pub struct OwnedEvent {
+    source: Arc<[u8]>,
+    version: FormatVersion,
+}
+
+impl OwnedEvent {
+    pub fn new(source: impl Into<Arc<[u8]>>) -> Result<Self, ParseError> {
+        Self::with_version(source, FormatVersion::default())
+    }
+
+    pub fn with_version(
+        source: impl Into<Arc<[u8]>>,
+        version: FormatVersion,
+    ) -> Result<Self, ParseError> {
+        let source = source.into();
+        let size = Event::with_version(&source, version)?.bytes().len();
+        // Don't hold on to anything past the end of the event
+        let source = if size == source.len() {
+            source
+        } else {
+            source[..size].into()
+        };
+        Ok(Self { source, version })
+    }
+
+    /// Borrow as an [`Event`].
+    pub fn as_ref(&self) -> Event<'_> {
+        // Checked in `new`
+        Event::with_version(&self.source, self.version).unwrap()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.source
+    }
+
+    pub fn size(&self) -> u32 {
+        self.as_ref().size()
+    }
+
+    pub fn type_id(&self) -> u32 {
+        self.as_ref().type_id()
+    }
+
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    pub fn body_header(&self) -> Result<BodyHeader<'_>, ParseError> {
+        self.as_ref().body_header()
+    }
+
+    pub fn ring_item(&self) -> Result<RingItem<'_>, ParseError> {
+        self.as_ref().ring_item()
+    }
+}
+
+impl<'s> Event<'s> {
+    /// Copy the bytes into an [`OwnedEvent`].
+    pub fn to_owned(&self) -> OwnedEvent {
+        OwnedEvent {
+            source: self.source.into(),
+            version: self.version,
+        }
+    }
+}
+
+impl<'s> From<Event<'s>> for OwnedEvent {
+    fn from(event: Event<'s>) -> Self {
+        event.to_owned()
+    }
+}
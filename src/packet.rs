@@ -0,0 +1,273 @@
+//! Parsing of packet-structured physics events, using the packet definitions from `PacketTypes`
+//! items.
+//!
+//! A packet is a 16-bit size (in 16-bit words, including the size and tag), a 16-bit tag, and a
+//! body, which may itself be made of packets. A `PacketTypes` item documents each packet with a
+//! string of the form `name:id:version:description`.
+
+use crate::{bits::TryFromSlice, error, Cursor, ParseError, PhysicsEvent, Text};
+use std::{collections::BTreeMap, fmt};
+
+/// An error encountered while parsing packets or their definitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// A packet definition string isn't of the form `name:id:version:description`.
+    BadDefinition(String),
+    /// A packet's size is too small to hold its size and tag.
+    BadPacketSize {
+        tag: u16,
+        size: u16,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::BadDefinition(x) => write!(f, "bad packet definition {:?}", x),
+            Self::BadPacketSize { tag, size } => {
+                write!(f, "packet {:#06x} has bad size {}", tag, size)
+            }
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// The definition of one kind of packet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PacketType {
+    pub name: String,
+    pub id: u16,
+    pub version: String,
+    pub description: String,
+}
+
+impl PacketType {
+    /// Parse a `name:id:version:description` string, where the id is decimal or `0x` hex.
+    ///
+    /// Anything after the third colon is part of the description.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let bad = || Error::BadDefinition(s.to_string());
+        let mut fields = s.splitn(4, ':');
+        let mut field = || fields.next().map(str::trim).ok_or_else(bad);
+        let name = field()?.to_string();
+        let id = field()?;
+        let version = field()?.to_string();
+        let description = field()?.to_string();
+        let id = match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => id.parse(),
+        }
+        .map_err(|_| bad())?;
+        Ok(Self {
+            name,
+            id,
+            version,
+            description,
+        })
+    }
+}
+
+/// A table of packet definitions, keyed by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PacketTypes {
+    types: BTreeMap<u16, PacketType>,
+}
+
+impl PacketTypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the definitions in a `PacketTypes` item.
+    pub fn from_text(text: &Text) -> Result<Self, Error> {
+        let mut types = Self::new();
+        types.extend_from_text(text)?;
+        Ok(types)
+    }
+
+    /// Add the definitions in a `PacketTypes` item, replacing any with the same id.
+    pub fn extend_from_text(&mut self, text: &Text) -> Result<(), Error> {
+        for s in text.strings()? {
+            self.insert(PacketType::parse(s)?);
+        }
+        Ok(())
+    }
+
+    /// Add a definition, returning the one it replaces.
+    pub fn insert(&mut self, packet_type: PacketType) -> Option<PacketType> {
+        self.types.insert(packet_type.id, packet_type)
+    }
+
+    pub fn get(&self, id: u16) -> Option<&PacketType> {
+        self.types.get(&id)
+    }
+
+    pub fn name(&self, id: u16) -> Option<&str> {
+        self.get(id).map(|x| x.name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// The definitions, in order of id.
+    pub fn iter(&self) -> impl Iterator<Item = &PacketType> {
+        self.types.values()
+    }
+}
+
+/// A single packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet<'s> {
+    source: &'s [u8],
+}
+
+impl<'s> Packet<'s> {
+    pub fn new(source: &'s [u8]) -> Result<Self, Error> {
+        let size = u16::try_from_slice(source, 0)?;
+        let tag = u16::try_from_slice(source, 2)?;
+        if size < 2 {
+            return Err(Error::BadPacketSize { tag, size });
+        }
+        let len = size as usize * 2;
+        let source = source.get(..len).ok_or(ParseError::TruncatedItem {
+            size: len,
+            available: source.len(),
+        })?;
+        Ok(Self { source })
+    }
+
+    // The accessors can't fail, since the size is checked in `new`
+
+    /// The size in 16-bit words, including the size and tag.
+    pub fn size(&self) -> u16 {
+        u16::try_from_slice(self.source, 0).unwrap()
+    }
+
+    pub fn tag(&self) -> u16 {
+        u16::try_from_slice(self.source, 2).unwrap()
+    }
+
+    pub fn bytes(&self) -> &'s [u8] {
+        self.source
+    }
+
+    /// The bytes after the size and tag.
+    pub fn body(&self) -> &'s [u8] {
+        &self.source[4..]
+    }
+
+    /// A cursor at the start of the body.
+    pub fn cursor(&self) -> Cursor<'s> {
+        Cursor::new(self.body())
+    }
+
+    /// The packets in the body.
+    pub fn packets(&self) -> Packets<'s> {
+        Packets::new(self.body())
+    }
+}
+
+/// An iterator over consecutive packets.
+#[derive(Debug, Clone, Copy)]
+pub struct Packets<'s> {
+    source: &'s [u8],
+}
+
+impl<'s> Packets<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self { source }
+    }
+
+    /// The packets in a physics event body, after its 32-bit size (in 16-bit words, including the
+    /// size itself).
+    pub fn from_physics_event(event: &PhysicsEvent<'s>) -> Result<Self, Error> {
+        let mut cursor = event.cursor();
+        let size = cursor.read_u32()? as usize * 2;
+        Ok(Self::new(cursor.read_bytes(size.saturating_sub(4))?))
+    }
+}
+
+impl<'s> Iterator for Packets<'s> {
+    type Item = Result<Packet<'s>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.source.is_empty() {
+            return None;
+        }
+        let result = Packet::new(self.source);
+        self.source = match &result {
+            Ok(packet) => &self.source[packet.bytes().len()..],
+            Err(_) => &[],
+        };
+        Some(result)
+    }
+}
+
+/// A packet found by a [`PacketWalker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Labeled<'s, 't> {
+    /// The number of packets this one is nested in.
+    pub depth: usize,
+    /// The documented name, if the packet's tag is in the table.
+    pub name: Option<&'t str>,
+    pub packet: Packet<'s>,
+}
+
+/// A walker that descends through nested packets, labeling each with its documented name.
+///
+/// A packet is treated as nested if its whole body parses as packets whose tags are all in the
+/// table.
+#[derive(Debug, Clone, Copy)]
+pub struct PacketWalker<'t> {
+    types: &'t PacketTypes,
+}
+
+impl<'t> PacketWalker<'t> {
+    pub fn new(types: &'t PacketTypes) -> Self {
+        Self { types }
+    }
+
+    /// Find all of the packets in a physics event, each followed by the packets nested in it.
+    pub fn walk<'s>(&self, event: &PhysicsEvent<'s>) -> Result<Vec<Labeled<'s, 't>>, Error> {
+        self.walk_packets(Packets::from_physics_event(event)?)
+    }
+
+    /// Find all of the packets in `packets`, each followed by the packets nested in it.
+    pub fn walk_packets<'s>(&self, packets: Packets<'s>) -> Result<Vec<Labeled<'s, 't>>, Error> {
+        let mut found = Vec::new();
+        for packet in packets {
+            self.walk_into(packet?, 0, &mut found);
+        }
+        Ok(found)
+    }
+
+    fn walk_into<'s>(&self, packet: Packet<'s>, depth: usize, found: &mut Vec<Labeled<'s, 't>>) {
+        found.push(Labeled {
+            depth,
+            name: self.types.name(packet.tag()),
+            packet,
+        });
+        if let Some(children) = self.nested(packet) {
+            for child in children {
+                self.walk_into(child, depth + 1, found);
+            }
+        }
+    }
+
+    /// The packets in the body of `packet`, if it's made of documented packets.
+    fn nested<'s>(&self, packet: Packet<'s>) -> Option<Vec<Packet<'s>>> {
+        let children = packet
+            .packets()
+            .map(|x| x.ok().filter(|x| self.types.get(x.tag()).is_some()))
+            .collect::<Option<Vec<_>>>()?;
+        (!children.is_empty()).then_some(children)
+    }
+}
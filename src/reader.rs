@@ -0,0 +1,167 @@
+use crate::{Event, FormatVersion, ParseError, ReadError, RingItem, DETECT_ITEMS};
+use std::io::{self, ErrorKind, Read};
+
+/// A reader of events from a stream, which holds only one event in memory at a time (apart from
+/// the first few, if the format has to be detected).
+///
+/// Since each event borrows from the reader's buffer, this can't be an [`Iterator`]. Use
+/// [`next_event`](Self::next_event) in a `while let` loop, or
+/// [`for_each_event`](Self::for_each_event), instead.
+#[derive(Debug)]
+pub struct NsclReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Items read ahead to detect the format, which come before the rest of the stream.
+    ahead: io::Cursor<Vec<u8>>,
+    /// An error hit while reading ahead, which comes after the items in `ahead`.
+    ahead_error: Option<ReadError>,
+    version: Option<FormatVersion>,
+    done: bool,
+}
+
+impl<R: Read> NsclReader<R> {
+    /// Read data in the format detected from the first few events (see
+    /// [`FormatVersion::detect`]), until a [`RingFormat`](crate::RingFormat) item says otherwise.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            ahead: io::Cursor::new(Vec::new()),
+            ahead_error: None,
+            version: None,
+            done: false,
+        }
+    }
+
+    /// Read data in the given format, until a [`RingFormat`](crate::RingFormat) item says
+    /// otherwise.
+    pub fn with_version(reader: R, version: FormatVersion) -> Self {
+        Self {
+            version: Some(version),
+            ..Self::new(reader)
+        }
+    }
+
+    /// The format version that the next event will be read with, if it is known yet.
+    pub fn version(&self) -> Option<FormatVersion> {
+        self.version
+    }
+
+    /// Read the next event, or `None` at the end of the stream.
+    ///
+    /// After an error, there's no way to know where the next event starts, so every following
+    /// call returns `None`.
+    pub fn next_event(&mut self) -> Option<Result<Event<'_>, ReadError>> {
+        if self.done {
+            return None;
+        }
+        if self.version.is_none() {
+            self.read_ahead();
+        }
+        match self.read_item() {
+            Ok(true) => {}
+            Ok(false) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+
+        let version = self.version.unwrap_or_default();
+        match Event::with_version(&self.buffer, version) {
+            Ok(event) => {
+                if let Ok(RingItem::RingFormat(ri)) = event.ring_item() {
+                    if let Some(version) = ri.major().ok().and_then(FormatVersion::from_major) {
+                        self.version = Some(version);
+                    }
+                }
+                Some(Ok(event))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e.into()))
+            }
+        }
+    }
+
+    /// Call `f` with every remaining event, stopping at the first error.
+    pub fn for_each_event<F: FnMut(Event<'_>)>(&mut self, mut f: F) -> Result<(), ReadError> {
+        while let Some(event) = self.next_event() {
+            f(event?);
+        }
+        Ok(())
+    }
+
+    /// Read the first few items and detect the format from them.
+    fn read_ahead(&mut self) {
+        let mut ahead = Vec::new();
+        for _ in 0..DETECT_ITEMS {
+            match read_item(&mut self.reader, &mut self.buffer) {
+                Ok(true) => ahead.extend_from_slice(&self.buffer),
+                Ok(false) => break,
+                Err(e) => {
+                    self.ahead_error = Some(e);
+                    break;
+                }
+            }
+        }
+        if !ahead.is_empty() {
+            self.version = Some(FormatVersion::detect(&ahead));
+        }
+        self.ahead = io::Cursor::new(ahead);
+    }
+
+    /// Read the next item into the buffer, returning `false` if the stream is at its end.
+    fn read_item(&mut self) -> Result<bool, ReadError> {
+        if self.ahead.position() < self.ahead.get_ref().len() as u64 {
+            return read_item(&mut self.ahead, &mut self.buffer);
+        }
+        if let Some(e) = self.ahead_error.take() {
+            return Err(e);
+        }
+        read_item(&mut self.reader, &mut self.buffer)
+    }
+}
+
+/// Read the next item from `reader` into `buffer`, returning `false` if the stream is at its end.
+fn read_item(mut reader: impl Read, buffer: &mut Vec<u8>) -> Result<bool, ReadError> {
+    let mut size = [0; 4];
+    let mut filled = 0;
+    while filled < size.len() {
+        match reader.read(&mut size[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    match filled {
+        0 => return Ok(false),
+        4 => {}
+        available => {
+            return Err(ParseError::TruncatedItem { size: 4, available }.into());
+        }
+    }
+
+    let size = u32::from_le_bytes(size) as usize;
+    if size == 0 {
+        return Err(ParseError::ZeroSize.into());
+    }
+    buffer.clear();
+    buffer.extend_from_slice(&(size as u32).to_le_bytes());
+    // Use `take` rather than allocating `size` bytes up front, in case the size is garbage
+    (&mut reader)
+        .take(size.saturating_sub(4) as u64)
+        .read_to_end(buffer)?;
+    if buffer.len() < size {
+        return Err(ParseError::TruncatedItem {
+            size,
+            available: buffer.len(),
+        }
+        .into());
+    }
+    Ok(true)
+}
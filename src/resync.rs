@@ -0,0 +1,111 @@
+use crate::{Event, FormatVersion, NsclData, ParseError, RingItem};
+use std::{fmt, ops::Range};
+
+/// A region of data that was skipped while resynchronizing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// The skipped bytes, relative to the start of the data.
+    pub range: Range<usize>,
+    /// The error that caused the region to be skipped.
+    pub error: ParseError,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "skipped bytes {}..{}: {}",
+            self.range.start, self.range.end, self.error
+        )
+    }
+}
+
+/// An iterator over events that skips forward to the next plausible event when it hits bad
+/// data.
+///
+/// This is created by [`NsclData::resync`].
+#[derive(Debug, Clone)]
+pub struct Resync<'s, F> {
+    data: NsclData<'s>,
+    on_skip: F,
+    known_types_only: bool,
+}
+
+impl<'s, F: FnMut(Skipped)> Resync<'s, F> {
+    pub(crate) fn new(data: NsclData<'s>, on_skip: F) -> Self {
+        Self {
+            data,
+            on_skip,
+            known_types_only: false,
+        }
+    }
+
+    /// Whether to skip items with types that aren't known, even where the previous item ended.
+    ///
+    /// By default, any item that parses there is kept, so newer or site-local types come through
+    /// as [`RingItem::Unknown`]. Items found by skipping forward must always have a known type,
+    /// since an arbitrary type is too easy to hit by chance, but that isn't enough when garbage
+    /// that happens to parse would swallow the good items after it.
+    pub fn known_types_only(mut self, known_types_only: bool) -> Self {
+        self.known_types_only = known_types_only;
+        self
+    }
+
+    /// The number of bytes from the start of the data to the next event.
+    pub fn offset(&self) -> usize {
+        self.data.offset()
+    }
+}
+
+impl<'s, F: FnMut(Skipped)> Iterator for Resync<'s, F> {
+    type Item = Event<'s>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.source.is_empty() {
+            let result = if self.known_types_only {
+                plausible_event(self.data.source, self.data.version)
+            } else {
+                checked_event(self.data.source, self.data.version)
+            };
+            match result {
+                Ok(event) => {
+                    self.data.accept(&event);
+                    return Some(event);
+                }
+                Err(error) => {
+                    let source = self.data.source;
+                    let skip = (1..source.len())
+                        .find(|&i| is_plausible(&source[i..], self.data.version))
+                        .unwrap_or(source.len());
+                    let start = self.data.offset();
+                    (self.on_skip)(Skipped {
+                        range: start..start + skip,
+                        error,
+                    });
+                    self.data.advance(skip);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parse an event, also making sure that its body header is sound.
+fn checked_event(source: &[u8], version: FormatVersion) -> Result<Event<'_>, ParseError> {
+    let event = Event::with_version(source, version)?;
+    event.ring_item()?;
+    Ok(event)
+}
+
+/// Parse an event, also making sure that it has one of the known types.
+fn plausible_event(source: &[u8], version: FormatVersion) -> Result<Event<'_>, ParseError> {
+    let event = checked_event(source, version)?;
+    match event.type_id() {
+        x if RingItem::is_known_type(x) => Ok(event),
+        x => Err(ParseError::UnknownType(x)),
+    }
+}
+
+/// Whether `source` looks like it starts with a ring item.
+fn is_plausible(source: &[u8], version: FormatVersion) -> bool {
+    plausible_event(source, version).is_ok()
+}
@@ -0,0 +1,441 @@
+//! Accumulation of periodic scaler readouts into totals and rates.
+//!
+//! Scalers are tracked per (source id, index). Readouts that aren't incremental are converted to
+//! increments by subtracting the previous readout, modulo the width of the counter, so counters
+//! that roll over are handled as long as they don't roll over more than once between readouts.
+//!
+//! A [`ChannelMap`] gives scalers names (and widths), so they can be reported by name. It can be
+//! read from a configuration file with one scaler per line:
+//!
+//! ```text
+//! # source_id index name [width]
+//! 7 0 clock
+//! 7 1 "live trigger" 24
+//!
+//! # A section sets the source id for the lines after it, which leave it out
+//! [8]
+//! 0 beam
+//! 1 scint_left 24
+//! ```
+
+use crate::{Event, ParseError, PeriodicScalers, RingItem};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::Path,
+};
+
+/// The default width of a scaler counter, in bits.
+pub const DEFAULT_WIDTH: u32 = 32;
+
+/// The increments from one scaler readout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub source_id: u32,
+    /// The start of the interval, in seconds since the start of the run.
+    pub start: f64,
+    /// The end of the interval, in seconds since the start of the run.
+    pub end: f64,
+    pub increments: Vec<u64>,
+}
+
+impl Interval {
+    /// The length of the interval, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// The rate of each scaler over the interval, in Hz, or `None` if the interval is empty.
+    pub fn rates(&self) -> Option<Vec<f64>> {
+        let duration = self.duration();
+        (duration > 0.0).then(|| {
+            self.increments
+                .iter()
+                .map(|x| *x as f64 / duration)
+                .collect()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Channel {
+    total: u64,
+    /// The last readout, masked to the width, for non-incremental scalers.
+    last: u64,
+    rate: Option<f64>,
+}
+
+/// An accumulator of scaler totals and rates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulator {
+    channels: BTreeMap<(u32, usize), Channel>,
+    default_width: u32,
+    widths: HashMap<(u32, usize), u32>,
+    names: ChannelMap,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            channels: BTreeMap::new(),
+            default_width: DEFAULT_WIDTH,
+            widths: HashMap::new(),
+            names: ChannelMap::new(),
+        }
+    }
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the width (in bits) of the counters that don't have their own width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` isn't in `1..=32`.
+    pub fn width(mut self, bits: u32) -> Self {
+        check_width(bits);
+        self.default_width = bits;
+        self
+    }
+
+    /// Set the width (in bits) of one counter, e.g. 24 for a 24-bit scaler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` isn't in `1..=32`.
+    pub fn channel_width(mut self, source_id: u32, index: usize, bits: u32) -> Self {
+        check_width(bits);
+        self.widths.insert((source_id, index), bits);
+        self
+    }
+
+    /// Name the scalers in `names`, and use the widths it gives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a width isn't in `1..=32`, which can only happen if it was inserted by hand
+    /// rather than parsed.
+    pub fn channel_map(mut self, names: ChannelMap) -> Self {
+        for (key, channel) in names.iter() {
+            if let Some(width) = channel.width {
+                check_width(width);
+                self.widths.insert(key, width);
+            }
+        }
+        self.names = names;
+        self
+    }
+
+    /// Add a scaler readout from `source_id`, returning its increments.
+    pub fn add(
+        &mut self,
+        source_id: u32,
+        scalers: &PeriodicScalers,
+    ) -> Result<Interval, ParseError> {
+        let start = scalers.interval_start()?.as_secs_f64();
+        let end = scalers.interval_end()?.as_secs_f64();
+        let is_incremental = scalers.is_incremental()?;
+        let duration = end - start;
+
+        let mut increments = Vec::new();
+        for (index, value) in scalers.scalers()?.into_iter().enumerate() {
+            let mask = self.mask(source_id, index);
+            let channel = self.channels.entry((source_id, index)).or_default();
+            let value = value as u64 & mask;
+            let increment = if is_incremental {
+                value
+            } else {
+                let increment = value.wrapping_sub(channel.last) & mask;
+                channel.last = value;
+                increment
+            };
+            channel.total += increment;
+            channel.rate = (duration > 0.0).then(|| increment as f64 / duration);
+            increments.push(increment);
+        }
+        Ok(Interval {
+            source_id,
+            start,
+            end,
+            increments,
+        })
+    }
+
+    /// Add the readout in `event` if it's a scaler item, or forget the previous non-incremental
+    /// readouts if it's a begin run item (since the counters are cleared then).
+    ///
+    /// The source id is from the body header, or 0 if there isn't one.
+    pub fn observe(&mut self, event: &Event) -> Result<Option<Interval>, ParseError> {
+        match event.ring_item()? {
+            RingItem::PeriodicScalers(scalers) => {
+                let source_id = event.body_header()?.source_id().unwrap_or(0);
+                self.add(source_id, &scalers).map(Some)
+            }
+            RingItem::BeginRun(_) => {
+                self.clear_readouts();
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Forget the previous non-incremental readouts, so the next readouts count from 0.
+    pub fn clear_readouts(&mut self) {
+        for channel in self.channels.values_mut() {
+            channel.last = 0;
+        }
+    }
+
+    /// The total count of a scaler.
+    pub fn total(&self, source_id: u32, index: usize) -> Option<u64> {
+        self.channels.get(&(source_id, index)).map(|x| x.total)
+    }
+
+    /// The rate of a scaler over its latest interval, in Hz.
+    pub fn rate(&self, source_id: u32, index: usize) -> Option<f64> {
+        self.channels.get(&(source_id, index))?.rate
+    }
+
+    /// The total count of each scaler, in order of (source id, index).
+    pub fn totals(&self) -> impl Iterator<Item = ((u32, usize), u64)> + '_ {
+        self.channels.iter().map(|(k, v)| (*k, v.total))
+    }
+
+    /// The name of a scaler, if it has one.
+    pub fn name(&self, source_id: u32, index: usize) -> Option<&str> {
+        self.names.name(source_id, index)
+    }
+
+    /// The total count of the scaler named `name`.
+    pub fn total_by_name(&self, name: &str) -> Option<u64> {
+        let (source_id, index) = self.names.find(name)?;
+        self.total(source_id, index)
+    }
+
+    /// The rate of the scaler named `name` over its latest interval, in Hz.
+    pub fn rate_by_name(&self, name: &str) -> Option<f64> {
+        let (source_id, index) = self.names.find(name)?;
+        self.rate(source_id, index)
+    }
+
+    /// The total count of each scaler, labeled by name where it has one, in order of (source id,
+    /// index).
+    pub fn named_totals(&self) -> impl Iterator<Item = (Label<'_>, u64)> + '_ {
+        self.channels
+            .iter()
+            .map(move |(k, v)| (self.names.label(k.0, k.1), v.total))
+    }
+
+    fn mask(&self, source_id: u32, index: usize) -> u64 {
+        let bits = self
+            .widths
+            .get(&(source_id, index))
+            .copied()
+            .unwrap_or(self.default_width);
+        (1u64 << bits) - 1
+    }
+}
+
+/// Check a counter width, since a width of 0 would mask every count to 0.
+fn check_width(bits: u32) {
+    assert!(
+        (1..=32).contains(&bits),
+        "scaler width {} isn't in 1..=32",
+        bits
+    );
+}
+
+/// How a scaler is reported: by name if it has one, or else by (source id, index).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Label<'a> {
+    Name(&'a str),
+    Index(u32, usize),
+}
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(x) => write!(f, "{}", x),
+            Self::Index(source_id, index) => write!(f, "{}:{}", source_id, index),
+        }
+    }
+}
+
+/// An error encountered while reading a channel map.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    Io(io::Error),
+    /// A line (numbered from 1) that can't be parsed.
+    BadLine {
+        line: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::BadLine { line, text } => write!(f, "bad line {}: {:?}", line, text),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The configuration of one scaler.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelConfig {
+    pub name: String,
+    /// The width of the counter in bits, if it isn't the default.
+    pub width: Option<u32>,
+}
+
+/// A map from (source id, index) to scaler names and widths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelMap {
+    channels: BTreeMap<(u32, usize), ChannelConfig>,
+}
+
+impl ChannelMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a configuration file in the format described in the [module docs](self).
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a configuration in the format described in the [module docs](self).
+    pub fn parse(s: &str) -> Result<Self, ConfigError> {
+        let mut map = Self::new();
+        let mut section = None;
+        for (i, text) in s.lines().enumerate() {
+            let bad = || ConfigError::BadLine {
+                line: i + 1,
+                text: text.to_string(),
+            };
+            let line = strip_comment(text).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(x) = line.strip_prefix('[') {
+                let x = x.strip_suffix(']').ok_or_else(bad)?;
+                section = Some(x.trim().parse().map_err(|_| bad())?);
+                continue;
+            }
+            let mut fields = split_fields(line).ok_or_else(bad)?.into_iter();
+            let source_id = match section {
+                Some(x) => x,
+                None => fields.next().ok_or_else(bad)?.parse().map_err(|_| bad())?,
+            };
+            let index = fields.next().ok_or_else(bad)?.parse().map_err(|_| bad())?;
+            let name = fields.next().ok_or_else(bad)?;
+            let width = match fields.next() {
+                Some(x) => match x.parse() {
+                    Ok(x @ 1..=32) => Some(x),
+                    _ => return Err(bad()),
+                },
+                None => None,
+            };
+            if fields.next().is_some() {
+                return Err(bad());
+            }
+            map.insert(source_id, index, ChannelConfig { name, width });
+        }
+        Ok(map)
+    }
+
+    /// Configure a scaler, returning its old configuration.
+    pub fn insert(
+        &mut self,
+        source_id: u32,
+        index: usize,
+        config: ChannelConfig,
+    ) -> Option<ChannelConfig> {
+        self.channels.insert((source_id, index), config)
+    }
+
+    pub fn get(&self, source_id: u32, index: usize) -> Option<&ChannelConfig> {
+        self.channels.get(&(source_id, index))
+    }
+
+    pub fn name(&self, source_id: u32, index: usize) -> Option<&str> {
+        self.get(source_id, index).map(|x| x.name.as_str())
+    }
+
+    /// How to report a scaler: by its name, or by (source id, index) if it doesn't have one.
+    pub fn label(&self, source_id: u32, index: usize) -> Label<'_> {
+        match self.name(source_id, index) {
+            Some(name) => Label::Name(name),
+            None => Label::Index(source_id, index),
+        }
+    }
+
+    /// The (source id, index) of the scaler named `name`.
+    pub fn find(&self, name: &str) -> Option<(u32, usize)> {
+        self.iter().find(|(_, x)| x.name == name).map(|(k, _)| k)
+    }
+
+    /// The scalers, in order of (source id, index).
+    pub fn iter(&self) -> impl Iterator<Item = ((u32, usize), &ChannelConfig)> {
+        self.channels.iter().map(|(k, v)| (*k, v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+/// Remove a `#` comment, unless it's in a quoted name.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Split a line at whitespace, keeping double-quoted fields together.
+fn split_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let (field, next) = match rest.strip_prefix('"') {
+            Some(x) => {
+                let end = x.find('"')?;
+                (&x[..end], &x[end + 1..])
+            }
+            None => rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+        };
+        fields.push(field.to_string());
+        rest = next.trim_start();
+    }
+    Some(fields)
+}
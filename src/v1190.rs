@@ -0,0 +1,238 @@
+//! Decoding of CAEN V1190 and V1290 multi-hit TDC data.
+//!
+//! Each event from a module starts with a global header and ends with a global trailer. In
+//! between, each TDC chip's measurements may be wrapped in a TDC header and trailer, and there may
+//! be TDC error words and an extended trigger time tag. The word type is in bits 27-31.
+
+use crate::{ParseError, PhysicsEvent};
+use std::{collections::BTreeMap, fmt};
+
+const MEASUREMENT: u32 = 0b00000;
+const TDC_HEADER: u32 = 0b00001;
+const TDC_TRAILER: u32 = 0b00011;
+const TDC_ERROR: u32 = 0b00100;
+const GLOBAL_HEADER: u32 = 0b01000;
+const GLOBAL_TRAILER: u32 = 0b10000;
+const TRIGGER_TIME_TAG: u32 = 0b10001;
+const FILLER: u32 = 0b11000;
+
+/// An error encountered while decoding V1190 or V1290 data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A word that isn't allowed where it is, e.g. a measurement before a global header.
+    UnexpectedWord(u32),
+    /// The data ended before the global trailer.
+    MissingTrailer,
+    /// The number of words doesn't match the count in a trailer.
+    WordCountMismatch { trailer: u32, words: u32 },
+    /// The global trailer has a different geographical address than the global header.
+    GeoMismatch { header: u32, trailer: u32 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedWord(x) => write!(f, "unexpected word {:#010x}", x),
+            Self::MissingTrailer => write!(f, "missing global trailer"),
+            Self::WordCountMismatch { trailer, words } => write!(
+                f,
+                "trailer word count is {}, but there are {} words",
+                trailer, words
+            ),
+            Self::GeoMismatch { header, trailer } => write!(
+                f,
+                "header geo is {}, but trailer geo is {}",
+                header, trailer
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The module, which determines how measurements are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Model {
+    /// 128 channels (64 for the V1190B) with 19-bit measurements.
+    V1190,
+    /// 32 channels (16 for the V1290N) with 21-bit measurements.
+    V1290,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    Leading,
+    Trailing,
+}
+
+/// A single measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    pub edge: Edge,
+    pub value: u32,
+}
+
+/// An error reported by one of the TDC chips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TdcError {
+    pub tdc: u32,
+    /// The error flags (bits 0-14), as described in the manual.
+    pub flags: u32,
+}
+
+/// The data from one module for one trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub geo: u32,
+    pub event_count: u32,
+    /// The 27-bit extended trigger time tag, if it is enabled.
+    pub trigger_time_tag: Option<u32>,
+    /// The hits on each channel that had any, in the order they were read out.
+    pub hits: BTreeMap<u32, Vec<Hit>>,
+    pub tdc_errors: Vec<TdcError>,
+    /// Whether the global trailer reports that a TDC had an error.
+    pub tdc_error: bool,
+    /// Whether the global trailer reports that the output buffer overflowed.
+    pub overflow: bool,
+    /// Whether the global trailer reports that triggers were lost.
+    pub trigger_lost: bool,
+}
+
+impl Event {
+    /// The hits on `channel`, which is empty if there were none.
+    pub fn channel(&self, channel: u32) -> &[Hit] {
+        self.hits.get(&channel).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// An iterator over the events in a stream of words, skipping any filler words between events.
+#[derive(Debug, Clone)]
+pub struct Events<I> {
+    words: I,
+    model: Model,
+}
+
+impl<I: Iterator<Item = u32>> Events<I> {
+    pub fn new(words: impl IntoIterator<IntoIter = I>, model: Model) -> Self {
+        Self {
+            words: words.into_iter(),
+            model,
+        }
+    }
+
+    fn read_event(&mut self, header: u32) -> Result<Event, Error> {
+        let geo = header & 0x1f;
+        let mut event = Event {
+            geo,
+            event_count: (header >> 5) & 0x3f_ffff,
+            trigger_time_tag: None,
+            hits: BTreeMap::new(),
+            tdc_errors: Vec::new(),
+            tdc_error: false,
+            overflow: false,
+            trigger_lost: false,
+        };
+        // Both word counts include the header and trailer
+        let mut words = 1;
+        let mut tdc_words = None;
+        loop {
+            let word = self.words.next().ok_or(Error::MissingTrailer)?;
+            words += 1;
+            if let Some(x) = &mut tdc_words {
+                *x += 1;
+            }
+            match word >> 27 {
+                MEASUREMENT => {
+                    let (channel, value) = match self.model {
+                        Model::V1190 => ((word >> 19) & 0x7f, word & 0x7_ffff),
+                        Model::V1290 => ((word >> 21) & 0x1f, word & 0x1f_ffff),
+                    };
+                    let edge = if word & (1 << 26) != 0 {
+                        Edge::Trailing
+                    } else {
+                        Edge::Leading
+                    };
+                    event
+                        .hits
+                        .entry(channel)
+                        .or_default()
+                        .push(Hit { edge, value });
+                }
+                TDC_HEADER if tdc_words.is_none() => tdc_words = Some(1),
+                TDC_TRAILER => {
+                    let count = word & 0xfff;
+                    match tdc_words.take() {
+                        Some(x) if x != count => {
+                            return Err(Error::WordCountMismatch {
+                                trailer: count,
+                                words: x,
+                            })
+                        }
+                        Some(_) => {}
+                        None => return Err(Error::UnexpectedWord(word)),
+                    }
+                }
+                TDC_ERROR => event.tdc_errors.push(TdcError {
+                    tdc: (word >> 24) & 0x3,
+                    flags: word & 0x7fff,
+                }),
+                TRIGGER_TIME_TAG => event.trigger_time_tag = Some(word & 0x7ff_ffff),
+                GLOBAL_TRAILER if tdc_words.is_none() => {
+                    let count = (word >> 5) & 0xffff;
+                    if count != words {
+                        return Err(Error::WordCountMismatch {
+                            trailer: count,
+                            words,
+                        });
+                    }
+                    if word & 0x1f != geo {
+                        return Err(Error::GeoMismatch {
+                            header: geo,
+                            trailer: word & 0x1f,
+                        });
+                    }
+                    event.tdc_error = word & (1 << 24) != 0;
+                    event.overflow = word & (1 << 25) != 0;
+                    event.trigger_lost = word & (1 << 26) != 0;
+                    return Ok(event);
+                }
+                _ => return Err(Error::UnexpectedWord(word)),
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = u32>> Iterator for Events<I> {
+    type Item = Result<Event, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let word = self.words.next()?;
+            let result = match word >> 27 {
+                FILLER => continue,
+                GLOBAL_HEADER => self.read_event(word),
+                _ => Err(Error::UnexpectedWord(word)),
+            };
+            if result.is_err() {
+                // The global header has no word count, so the rest of a bad event can't be skipped
+                self.words.by_ref().for_each(drop);
+            }
+            return Some(result);
+        }
+    }
+}
+
+/// An iterator over the events in the body of a physics event, after its 32-bit size (in 16-bit
+/// words, including the size itself).
+pub fn events<'s>(
+    event: &PhysicsEvent<'s>,
+    model: Model,
+) -> Result<Events<impl Iterator<Item = u32> + 's>, ParseError> {
+    let mut cursor = event.cursor();
+    let size = cursor.read_u32()? as usize * 2;
+    let words = cursor
+        .read_bytes(size.saturating_sub(4))?
+        .chunks_exact(4)
+        .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]));
+    Ok(Events::new(words, model))
+}
@@ -0,0 +1,578 @@
+//! Writing of ring items.
+//!
+//! Each kind of ring item has a builder, which holds the item's fields and encodes them in the
+//! layout of whichever [`FormatVersion`] is being written.
+
+use crate::{Event, FormatVersion, OwnedEvent};
+use std::io::{self, Write};
+
+/// The longest title in a state change item, which is followed by at least one NUL.
+const TITLE_MAXSIZE: usize = 80;
+
+/// Something that can be encoded as the body of a ring item.
+pub trait Encode {
+    /// The ring item type.
+    fn type_id(&self) -> u32;
+
+    /// Append the body of the item (everything after the body header) to `out`.
+    fn encode_body(&self, version: FormatVersion, out: &mut Vec<u8>);
+
+    /// Encode a complete ring item into an [`OwnedEvent`].
+    ///
+    /// This fails in the same way as [`encode`].
+    fn to_event(
+        &self,
+        body_header: Option<&BodyHeaderBuilder>,
+        version: FormatVersion,
+    ) -> io::Result<OwnedEvent> {
+        let mut out = Vec::new();
+        encode(&mut out, body_header, self, version)?;
+        // The encoding is always a valid event
+        Ok(OwnedEvent::with_version(out, version).unwrap())
+    }
+}
+
+/// Append a complete ring item to `out`.
+///
+/// This fails with [`io::ErrorKind::InvalidInput`] if there is a body header when encoding 10.x,
+/// which has no body headers, rather than dropping it without a word. Nothing is appended then.
+pub fn encode<E: Encode + ?Sized>(
+    out: &mut Vec<u8>,
+    body_header: Option<&BodyHeaderBuilder>,
+    item: &E,
+    version: FormatVersion,
+) -> io::Result<()> {
+    if version == FormatVersion::V10 && body_header.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "10.x items have no body header",
+        ));
+    }
+    let start = out.len();
+    // The size is filled in at the end
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&item.type_id().to_le_bytes());
+    match (version, body_header) {
+        (FormatVersion::V10, _) => {}
+        (FormatVersion::V11, None) => out.extend_from_slice(&0u32.to_le_bytes()),
+        (FormatVersion::V12, None) => out.extend_from_slice(&4u32.to_le_bytes()),
+        (_, Some(bh)) => bh.encode(out),
+    }
+    item.encode_body(version, out);
+    let size = (out.len() - start) as u32;
+    out[start..][..4].copy_from_slice(&size.to_le_bytes());
+    Ok(())
+}
+
+/// A writer of ring items to any [`Write`].
+#[derive(Debug)]
+pub struct RingItemWriter<W> {
+    writer: W,
+    version: FormatVersion,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> RingItemWriter<W> {
+    /// Write items in the 11.x format.
+    pub fn new(writer: W) -> Self {
+        Self::with_version(writer, FormatVersion::default())
+    }
+
+    /// Write items in the given format.
+    ///
+    /// This doesn't write a [`RingFormat`](crate::RingFormat) item by itself, so to make the
+    /// format explicit, start by writing a [`RingFormatBuilder`].
+    pub fn with_version(writer: W, version: FormatVersion) -> Self {
+        Self {
+            writer,
+            version,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Write an item with an empty body header.
+    pub fn write<E: Encode + ?Sized>(&mut self, item: &E) -> io::Result<()> {
+        self.write_item(None, item)
+    }
+
+    /// Write an item with a full body header.
+    ///
+    /// This fails with [`io::ErrorKind::InvalidInput`] when writing 10.x, which has no body
+    /// headers, rather than dropping the body header without a word.
+    pub fn write_with_body_header<E: Encode + ?Sized>(
+        &mut self,
+        body_header: &BodyHeaderBuilder,
+        item: &E,
+    ) -> io::Result<()> {
+        self.write_item(Some(body_header), item)
+    }
+
+    /// Write an existing event as it is, e.g. when filtering data.
+    ///
+    /// The event isn't converted, so it should be in the same format as the writer.
+    pub fn write_event(&mut self, event: &Event) -> io::Result<()> {
+        self.writer.write_all(event.bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_item<E: Encode + ?Sized>(
+        &mut self,
+        body_header: Option<&BodyHeaderBuilder>,
+        item: &E,
+    ) -> io::Result<()> {
+        self.buffer.clear();
+        encode(&mut self.buffer, body_header, item, self.version)?;
+        self.writer.write_all(&self.buffer)
+    }
+}
+
+/// The fields of a full body header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BodyHeaderBuilder {
+    timestamp: u64,
+    source_id: u32,
+    barrier_type: u32,
+    extension: Vec<u8>,
+}
+
+impl BodyHeaderBuilder {
+    pub fn new(timestamp: u64, source_id: u32) -> Self {
+        Self {
+            timestamp,
+            source_id,
+            ..Self::default()
+        }
+    }
+
+    pub fn barrier_type(mut self, barrier_type: u32) -> Self {
+        self.barrier_type = barrier_type;
+        self
+    }
+
+    pub fn extension(mut self, extension: Vec<u8>) -> Self {
+        self.extension = extension;
+        self
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(20 + self.extension.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&self.source_id.to_le_bytes());
+        out.extend_from_slice(&self.barrier_type.to_le_bytes());
+        out.extend_from_slice(&self.extension);
+    }
+}
+
+/// A begin, end, pause, resume, or abnormal end run item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChangeBuilder {
+    type_id: u32,
+    run_number: u32,
+    time_offset: u32,
+    timestamp: u32,
+    offset_divisor: u32,
+    original_sid: u32,
+    title: String,
+}
+
+impl StateChangeBuilder {
+    fn new(type_id: u32, run_number: u32) -> Self {
+        Self {
+            type_id,
+            run_number,
+            time_offset: 0,
+            timestamp: 0,
+            offset_divisor: 1,
+            original_sid: 0,
+            title: String::new(),
+        }
+    }
+
+    pub fn begin_run(run_number: u32) -> Self {
+        Self::new(1, run_number)
+    }
+
+    pub fn end_run(run_number: u32) -> Self {
+        Self::new(2, run_number)
+    }
+
+    pub fn pause_run(run_number: u32) -> Self {
+        Self::new(3, run_number)
+    }
+
+    pub fn resume_run(run_number: u32) -> Self {
+        Self::new(4, run_number)
+    }
+
+    pub fn abnormal_end_run(run_number: u32) -> Self {
+        Self::new(5, run_number)
+    }
+
+    pub fn time_offset(mut self, time_offset: u32, offset_divisor: u32) -> Self {
+        self.time_offset = time_offset;
+        self.offset_divisor = offset_divisor;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn original_sid(mut self, original_sid: u32) -> Self {
+        self.original_sid = original_sid;
+        self
+    }
+
+    /// Set the title, which is cut short if it doesn't fit in 80 bytes.
+    pub fn title(mut self, title: &str) -> Self {
+        let mut end = title.len().min(TITLE_MAXSIZE);
+        while !title.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.title = title[..end].to_string();
+        self
+    }
+}
+
+impl Encode for StateChangeBuilder {
+    fn type_id(&self) -> u32 {
+        self.type_id
+    }
+
+    fn encode_body(&self, version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.run_number.to_le_bytes());
+        out.extend_from_slice(&self.time_offset.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        if version != FormatVersion::V10 {
+            out.extend_from_slice(&self.offset_divisor.to_le_bytes());
+        }
+        if version == FormatVersion::V12 {
+            out.extend_from_slice(&self.original_sid.to_le_bytes());
+        }
+        // The length is limited in `title`, so there's always a terminating NUL, as in NSCLDAQ's
+        // `char s_title[TITLE_MAXSIZE + 1]`
+        out.extend_from_slice(self.title.as_bytes());
+        out.resize(out.len() + TITLE_MAXSIZE + 1 - self.title.len(), 0);
+    }
+}
+
+/// A packet types or monitored variables item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextBuilder {
+    type_id: u32,
+    time_offset: u32,
+    timestamp: u32,
+    offset_divisor: u32,
+    original_sid: u32,
+    strings: Vec<String>,
+}
+
+impl TextBuilder {
+    fn new(type_id: u32) -> Self {
+        Self {
+            type_id,
+            time_offset: 0,
+            timestamp: 0,
+            offset_divisor: 1,
+            original_sid: 0,
+            strings: Vec::new(),
+        }
+    }
+
+    pub fn packet_types() -> Self {
+        Self::new(10)
+    }
+
+    pub fn monitored_variables() -> Self {
+        Self::new(11)
+    }
+
+    pub fn time_offset(mut self, time_offset: u32, offset_divisor: u32) -> Self {
+        self.time_offset = time_offset;
+        self.offset_divisor = offset_divisor;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn original_sid(mut self, original_sid: u32) -> Self {
+        self.original_sid = original_sid;
+        self
+    }
+
+    /// Add a string.
+    ///
+    /// The strings are separated by NULs in the item, so a string that contains NULs is split
+    /// into several at them, and read back that way.
+    pub fn string(mut self, string: impl Into<String>) -> Self {
+        let string = string.into();
+        if string.contains('\0') {
+            self.strings.extend(string.split('\0').map(str::to_string));
+        } else {
+            self.strings.push(string);
+        }
+        self
+    }
+}
+
+impl Encode for TextBuilder {
+    fn type_id(&self) -> u32 {
+        self.type_id
+    }
+
+    fn encode_body(&self, version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.time_offset.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&(self.strings.len() as u32).to_le_bytes());
+        if version != FormatVersion::V10 {
+            out.extend_from_slice(&self.offset_divisor.to_le_bytes());
+        }
+        if version == FormatVersion::V12 {
+            out.extend_from_slice(&self.original_sid.to_le_bytes());
+        }
+        for string in &self.strings {
+            out.extend_from_slice(string.as_bytes());
+            out.push(0);
+        }
+    }
+}
+
+/// A ring format item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingFormatBuilder {
+    major: u16,
+    minor: u16,
+}
+
+impl RingFormatBuilder {
+    /// The ring format item for `version`.
+    pub fn new(version: FormatVersion) -> Self {
+        let major = match version {
+            FormatVersion::V10 => 10,
+            FormatVersion::V11 => 11,
+            FormatVersion::V12 => 12,
+        };
+        Self { major, minor: 0 }
+    }
+}
+
+impl Encode for RingFormatBuilder {
+    fn type_id(&self) -> u32 {
+        12
+    }
+
+    fn encode_body(&self, _version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.major.to_le_bytes());
+        out.extend_from_slice(&self.minor.to_le_bytes());
+    }
+}
+
+/// A periodic scalers item.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeriodicScalersBuilder {
+    interval_start_offset: u32,
+    interval_end_offset: u32,
+    timestamp: u32,
+    interval_divisor: u32,
+    is_incremental: bool,
+    original_sid: u32,
+    scalers: Vec<u32>,
+}
+
+impl PeriodicScalersBuilder {
+    pub fn new(scalers: Vec<u32>) -> Self {
+        Self {
+            interval_divisor: 1,
+            is_incremental: true,
+            scalers,
+            ..Self::default()
+        }
+    }
+
+    pub fn interval(mut self, start_offset: u32, end_offset: u32, divisor: u32) -> Self {
+        self.interval_start_offset = start_offset;
+        self.interval_end_offset = end_offset;
+        self.interval_divisor = divisor;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn is_incremental(mut self, is_incremental: bool) -> Self {
+        self.is_incremental = is_incremental;
+        self
+    }
+
+    pub fn original_sid(mut self, original_sid: u32) -> Self {
+        self.original_sid = original_sid;
+        self
+    }
+}
+
+impl Encode for PeriodicScalersBuilder {
+    fn type_id(&self) -> u32 {
+        20
+    }
+
+    fn encode_body(&self, version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.interval_start_offset.to_le_bytes());
+        out.extend_from_slice(&self.interval_end_offset.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        if version != FormatVersion::V10 {
+            out.extend_from_slice(&self.interval_divisor.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.scalers.len() as u32).to_le_bytes());
+        if version != FormatVersion::V10 {
+            out.extend_from_slice(&(self.is_incremental as u32).to_le_bytes());
+        }
+        if version == FormatVersion::V12 {
+            out.extend_from_slice(&self.original_sid.to_le_bytes());
+        }
+        for scaler in &self.scalers {
+            out.extend_from_slice(&scaler.to_le_bytes());
+        }
+    }
+}
+
+/// A physics event count item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhysicsEventCountBuilder {
+    time_offset: u32,
+    offset_divisor: u32,
+    original_sid: u32,
+    timestamp: u32,
+    event_count: u64,
+}
+
+impl PhysicsEventCountBuilder {
+    pub fn new(event_count: u64) -> Self {
+        Self {
+            offset_divisor: 1,
+            event_count,
+            ..Self::default()
+        }
+    }
+
+    pub fn time_offset(mut self, time_offset: u32, offset_divisor: u32) -> Self {
+        self.time_offset = time_offset;
+        self.offset_divisor = offset_divisor;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn original_sid(mut self, original_sid: u32) -> Self {
+        self.original_sid = original_sid;
+        self
+    }
+}
+
+impl Encode for PhysicsEventCountBuilder {
+    fn type_id(&self) -> u32 {
+        31
+    }
+
+    fn encode_body(&self, version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.time_offset.to_le_bytes());
+        if version != FormatVersion::V10 {
+            out.extend_from_slice(&self.offset_divisor.to_le_bytes());
+        }
+        if version == FormatVersion::V12 {
+            out.extend_from_slice(&self.original_sid.to_le_bytes());
+        }
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&self.event_count.to_le_bytes());
+    }
+}
+
+/// An event builder glom info item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvbGlomInfoBuilder {
+    coincident_ticks: u64,
+    is_building: bool,
+    timestamp_policy: u16,
+}
+
+impl EvbGlomInfoBuilder {
+    pub fn new(coincident_ticks: u64, is_building: bool, timestamp_policy: u16) -> Self {
+        Self {
+            coincident_ticks,
+            is_building,
+            timestamp_policy,
+        }
+    }
+}
+
+impl Encode for EvbGlomInfoBuilder {
+    fn type_id(&self) -> u32 {
+        42
+    }
+
+    fn encode_body(&self, _version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.coincident_ticks.to_le_bytes());
+        out.extend_from_slice(&(self.is_building as u16).to_le_bytes());
+        out.extend_from_slice(&self.timestamp_policy.to_le_bytes());
+    }
+}
+
+/// An item whose body is written as it is: a physics event, event builder fragment, event
+/// builder unknown payload, user item, or anything else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBuilder {
+    type_id: u32,
+    body: Vec<u8>,
+}
+
+impl RawBuilder {
+    pub fn new(type_id: u32, body: Vec<u8>) -> Self {
+        Self { type_id, body }
+    }
+
+    pub fn physics_event(body: Vec<u8>) -> Self {
+        Self::new(30, body)
+    }
+
+    /// An event builder fragment, whose body is usually a complete ring item.
+    pub fn evb_fragment(body: Vec<u8>) -> Self {
+        Self::new(40, body)
+    }
+
+    pub fn evb_unknown_payload(body: Vec<u8>) -> Self {
+        Self::new(41, body)
+    }
+}
+
+impl Encode for RawBuilder {
+    fn type_id(&self) -> u32 {
+        self.type_id
+    }
+
+    fn encode_body(&self, _version: FormatVersion, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.body);
+    }
+}
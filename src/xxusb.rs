@@ -0,0 +1,215 @@
+//! Unwrapping of data read out through the Wiener VM-USB and CC-USB ("XX-USB") controllers.
+//!
+//! The data from a stack is preceded by a 16-bit header: the number of 16-bit words that follow
+//! (bits 0-11), a continuation bit (bit 12), and the stack that was executed (bits 13-15). An event
+//! that is too long for one segment is split into several, all but the last having the
+//! continuation bit set.
+//!
+//! CAMAC reads through the CC-USB in 24-bit mode produce two 16-bit words per read, with the Q and X
+//! responses in bits 24 and 25 of the combined word.
+
+use crate::{error, Cursor, ParseError, PhysicsEvent};
+use std::fmt;
+
+/// An error encountered while unwrapping XX-USB data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    /// The data ended while the last segment's continuation bit was set.
+    MissingContinuation,
+    /// A continuation segment is from a different stack than the first segment.
+    StackMismatch {
+        first: u16,
+        segment: u16,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::MissingContinuation => write!(f, "missing continuation segment"),
+            Self::StackMismatch { first, segment } => write!(
+                f,
+                "continuation of stack {} is from stack {}",
+                first, segment
+            ),
+        }
+    }
+}
+
+error::impl_parse_wrapper!(Error);
+
+/// The data following one stack header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<'s> {
+    pub stack_id: u16,
+    /// Whether the event continues in the next segment.
+    pub continued: bool,
+    pub data: &'s [u8],
+}
+
+/// An iterator over the segments in some data.
+#[derive(Debug, Clone, Copy)]
+pub struct Segments<'s> {
+    cursor: Cursor<'s>,
+}
+
+impl<'s> Segments<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self {
+            cursor: Cursor::new(source),
+        }
+    }
+}
+
+impl<'s> Iterator for Segments<'s> {
+    type Item = Result<Segment<'s>, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor.is_empty() {
+            return None;
+        }
+        let result = self.cursor.read_u16().and_then(|header| {
+            let words = (header & 0xfff) as usize;
+            Ok(Segment {
+                stack_id: header >> 13,
+                continued: header & (1 << 12) != 0,
+                data: self.cursor.read_bytes(words * 2)?,
+            })
+        });
+        if result.is_err() {
+            self.cursor = Cursor::new(&[]);
+        }
+        Some(result)
+    }
+}
+
+/// The data from one execution of a stack, with any continuation segments joined together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    pub stack_id: u16,
+    pub data: Vec<u8>,
+}
+
+impl Stack {
+    /// A cursor at the start of the data.
+    pub fn cursor(&self) -> Cursor<'_> {
+        Cursor::new(&self.data)
+    }
+
+    /// The data as 16-bit words.
+    pub fn words16(&self) -> impl Iterator<Item = u16> + '_ {
+        self.data
+            .chunks_exact(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+    }
+
+    /// The data as 32-bit words, ignoring any partial word at the end.
+    pub fn words32(&self) -> impl Iterator<Item = u32> + '_ {
+        self.data
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+    }
+
+    /// Split the data at each 16-bit `marker` word (e.g. one added by a marker command after
+    /// each module), which gives the data for each module.
+    ///
+    /// The markers themselves aren't included, and a final empty part (after a trailing marker)
+    /// is dropped.
+    pub fn split(&self, marker: u16) -> Vec<&[u8]> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (i, word) in self.words16().enumerate() {
+            if word == marker {
+                parts.push(&self.data[start..i * 2]);
+                start = i * 2 + 2;
+            }
+        }
+        if start < self.data.len() {
+            parts.push(&self.data[start..]);
+        }
+        parts
+    }
+
+    /// The data as CAMAC 24-bit reads.
+    pub fn camac(&self) -> impl Iterator<Item = CamacDatum> + '_ {
+        self.words32().map(CamacDatum::from_word)
+    }
+}
+
+/// An iterator over the stacks in some data.
+#[derive(Debug, Clone, Copy)]
+pub struct Stacks<'s> {
+    segments: Segments<'s>,
+}
+
+impl<'s> Stacks<'s> {
+    pub fn new(source: &'s [u8]) -> Self {
+        Self {
+            segments: Segments::new(source),
+        }
+    }
+
+    fn read_stack(&mut self, first: Segment) -> Result<Stack, Error> {
+        let mut data = first.data.to_vec();
+        let mut continued = first.continued;
+        while continued {
+            let segment = self.segments.next().ok_or(Error::MissingContinuation)??;
+            if segment.stack_id != first.stack_id {
+                return Err(Error::StackMismatch {
+                    first: first.stack_id,
+                    segment: segment.stack_id,
+                });
+            }
+            data.extend_from_slice(segment.data);
+            continued = segment.continued;
+        }
+        Ok(Stack {
+            stack_id: first.stack_id,
+            data,
+        })
+    }
+}
+
+impl<'s> Iterator for Stacks<'s> {
+    type Item = Result<Stack, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.segments.next()? {
+            Ok(first) => self.read_stack(first),
+            Err(e) => Err(e.into()),
+        };
+        if result.is_err() {
+            self.segments = Segments::new(&[]);
+        }
+        Some(result)
+    }
+}
+
+/// Unwrap the stack data in a physics event, which usually holds exactly one stack.
+pub fn stack(event: &PhysicsEvent) -> Result<Stack, Error> {
+    Stacks::new(event.bytes())
+        .next()
+        .unwrap_or(Err(Error::Parse(ParseError::OutOfBounds {
+            offset: 0,
+            len: 2,
+        })))
+}
+
+/// A single CAMAC read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CamacDatum {
+    pub value: u32,
+    pub q: bool,
+    pub x: bool,
+}
+
+impl CamacDatum {
+    pub fn from_word(word: u32) -> Self {
+        Self {
+            value: word & 0xff_ffff,
+            q: word & (1 << 24) != 0,
+            x: word & (1 << 25) != 0,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{item, words32};
+use nscl_evt::{Event, ParseError};
+
+fn body_header(size: u32, extension: &[u8]) -> Vec<u8> {
+    let mut v = words32(&[size]);
+    v.extend(5u64.to_le_bytes());
+    v.extend(words32(&[3, 1]));
+    v.extend(extension);
+    v
+}
+
+#[test]
+fn plain() {
+    let data = item(30, &body_header(20, &[]), &[1, 2]);
+    let e = Event::new(&data).unwrap();
+    let bh = e.body_header().unwrap();
+    assert_eq!(bh.size(), 20);
+    assert_eq!(bh.timestamp(), Some(5));
+    assert_eq!(bh.source_id(), Some(3));
+    assert_eq!(bh.barrier_type(), Some(1));
+    assert_eq!(bh.extension(), Some(&[][..]));
+    assert_eq!(e.ring_item().unwrap().bytes(), &[1, 2]);
+}
+
+#[test]
+fn extended() {
+    let data = item(30, &body_header(24, &[9, 9, 9, 9]), &[1, 2]);
+    let e = Event::new(&data).unwrap();
+    let bh = e.body_header().unwrap();
+    assert_eq!(bh.size(), 24);
+    assert_eq!(bh.source_id(), Some(3));
+    assert_eq!(bh.extension(), Some(&[9u8, 9, 9, 9][..]));
+    assert_eq!(e.ring_item().unwrap().bytes(), &[1, 2]);
+}
+
+#[test]
+fn extension_past_the_end() {
+    let data = item(30, &body_header(40, &[9, 9, 9, 9]), &[1, 2]);
+    let e = Event::new(&data).unwrap();
+    assert!(matches!(
+        e.body_header(),
+        Err(ParseError::OutOfBounds { offset: 0, len: 40 })
+    ));
+    assert!(e.ring_item().is_err());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::words32;
+use nscl_evt::{
+    caen::{Blocks, Error},
+    writer::{Encode, RawBuilder},
+    FormatVersion, ParseError,
+};
+
+const INVALID: u32 = 0x0600_0000;
+
+fn header(geo: u32, crate_id: u32, count: u32) -> u32 {
+    (geo << 27) | (2 << 24) | (crate_id << 16) | (count << 8)
+}
+
+fn datum(geo: u32, channel: u32, value: u32) -> u32 {
+    (geo << 27) | (channel << 16) | value
+}
+
+fn end_of_block(geo: u32, event_counter: u32) -> u32 {
+    (geo << 27) | (4 << 24) | event_counter
+}
+
+fn two_blocks() -> Vec<u32> {
+    vec![
+        INVALID,
+        header(3, 5, 2),
+        datum(3, 1, 100),
+        datum(3, 7, (1 << 12) | 0xfff),
+        end_of_block(3, 42),
+        INVALID,
+        header(4, 5, 1),
+        datum(4, 0, 1 << 13),
+        end_of_block(4, 42),
+    ]
+}
+
+#[test]
+fn blocks() {
+    let data = words32(&two_blocks());
+    let blocks: Vec<_> = Blocks::new(&data).map(Result::unwrap).collect();
+    assert_eq!(blocks.len(), 2);
+    let b = &blocks[0];
+    assert_eq!((b.geo, b.crate_id, b.event_counter), (3, 5, 42));
+    assert_eq!(b.data.len(), 2);
+    assert_eq!(b.channel(1).unwrap().value, 100);
+    assert!(!b.channel(1).unwrap().overflow);
+    assert!(b.channel(7).unwrap().overflow);
+    assert!(b.channel(2).is_none());
+    assert!(blocks[1].channel(0).unwrap().underflow);
+}
+
+#[test]
+fn from_physics_event() {
+    let mut body = words32(&[0]);
+    body.extend(words32(&two_blocks()));
+    let size = (body.len() / 2) as u32;
+    body[..4].copy_from_slice(&size.to_le_bytes());
+    // Anything past the size isn't part of the blocks
+    body.extend(words32(&[0xdead_beef]));
+    let e = RawBuilder::physics_event(body)
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let blocks: Vec<_> = Blocks::from_physics_event(&ri)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(blocks.len(), 2);
+
+    let e = RawBuilder::physics_event(words32(&[100]))
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert!(matches!(
+        Blocks::from_physics_event(&ri),
+        Err(Error::Parse(ParseError::OutOfBounds { .. }))
+    ));
+}
+
+#[test]
+fn errors() {
+    let words = two_blocks();
+    let first = |n: usize| words32(&words[..n]);
+    assert_eq!(
+        Blocks::new(&first(4)).next(),
+        Some(Err(Error::MissingEndOfBlock))
+    );
+
+    let mut bad = words.clone();
+    bad[1] = header(3, 5, 3);
+    assert_eq!(
+        Blocks::new(&words32(&bad)).next(),
+        Some(Err(Error::CountMismatch { header: 3, data: 2 }))
+    );
+
+    let mut bad = words.clone();
+    bad[3] = datum(9, 7, 0);
+    assert_eq!(
+        Blocks::new(&words32(&bad)).next(),
+        Some(Err(Error::GeoMismatch { header: 3, word: 9 }))
+    );
+
+    // An error ends the iteration
+    let mut bad = words.clone();
+    bad[0] = datum(3, 1, 100);
+    let results: Vec<_> = Blocks::new(&words32(&bad)).collect();
+    assert_eq!(results, vec![Err(Error::UnexpectedWord(bad[0]))]);
+
+    assert_eq!(
+        Blocks::new(&[0, 0]).next(),
+        Some(Err(Error::Parse(ParseError::OutOfBounds {
+            offset: 0,
+            len: 4
+        })))
+    );
+}
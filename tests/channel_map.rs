@@ -0,0 +1,134 @@
+use nscl_evt::{
+    scaler::{Accumulator, ChannelConfig, ChannelMap, ConfigError, Label},
+    writer::{BodyHeaderBuilder, PeriodicScalersBuilder, RingFormatBuilder},
+    FormatVersion, NsclData, RingItemWriter,
+};
+
+const CONFIG: &str = r#"
+# source_id index name [width]
+7 0 clock
+7 1 "live trigger" 24   # a comment
+7 2 "not # a comment"
+
+[8]
+0 beam
+  1   scint_left   24
+"#;
+
+fn config(name: &str, width: Option<u32>) -> ChannelConfig {
+    ChannelConfig {
+        name: name.to_string(),
+        width,
+    }
+}
+
+#[test]
+fn parse() {
+    let map = ChannelMap::parse(CONFIG).unwrap();
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.get(7, 0), Some(&config("clock", None)));
+    assert_eq!(map.get(7, 1), Some(&config("live trigger", Some(24))));
+    assert_eq!(map.name(7, 2), Some("not # a comment"));
+    assert_eq!(map.get(8, 0), Some(&config("beam", None)));
+    assert_eq!(map.get(8, 1), Some(&config("scint_left", Some(24))));
+    assert_eq!(map.find("beam"), Some((8, 0)));
+    assert_eq!(map.find("nothing"), None);
+    assert_eq!(map.label(8, 0), Label::Name("beam"));
+    assert_eq!(map.label(8, 2), Label::Index(8, 2));
+    let keys: Vec<_> = map.iter().map(|x| x.0).collect();
+    assert_eq!(keys, vec![(7, 0), (7, 1), (7, 2), (8, 0), (8, 1)]);
+}
+
+#[test]
+fn later_lines_replace_earlier_ones() {
+    let map = ChannelMap::parse("1 0 a\n[1]\n0 b 16").unwrap();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(1, 0), Some(&config("b", Some(16))));
+}
+
+#[test]
+fn empty() {
+    assert!(ChannelMap::parse("").unwrap().is_empty());
+    assert!(ChannelMap::parse("# nothing\n\n   \n").unwrap().is_empty());
+}
+
+#[test]
+fn bad_lines() {
+    for (s, line) in [
+        ("7 0", 1),
+        ("7", 1),
+        ("x 0 name", 1),
+        ("7 x name", 1),
+        ("7 0 name 0", 1),
+        ("7 0 name 33", 1),
+        ("7 0 name wide", 1),
+        ("7 0 name 24 extra", 1),
+        ("7 0 \"unterminated", 1),
+        ("7 0 a\n[8", 2),
+        ("7 0 a\n\n[x]", 3),
+        // A section leaves out the source id, so this has an extra field
+        ("[8]\n7 0 name", 2),
+    ] {
+        match ChannelMap::parse(s) {
+            Err(ConfigError::BadLine { line: l, text }) => {
+                assert_eq!(l, line, "{:?}", s);
+                assert_eq!(text, s.lines().nth(line - 1).unwrap());
+            }
+            x => panic!("{:?} parsed as {:?}", s, x),
+        }
+    }
+}
+
+#[test]
+fn missing_file() {
+    let e = ChannelMap::from_file("/nonexistent/channels.txt").unwrap_err();
+    assert!(matches!(e, ConfigError::Io(_)));
+}
+
+#[test]
+fn named_totals() {
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    for (source_id, scalers) in [(7, vec![1, 2, 3]), (8, vec![4, 5])] {
+        w.write_with_body_header(
+            &BodyHeaderBuilder::new(0, source_id),
+            &PeriodicScalersBuilder::new(scalers).interval(0, 1, 1),
+        )
+        .unwrap();
+    }
+    let data = w.into_inner();
+
+    let map = ChannelMap::parse(CONFIG).unwrap();
+    let mut accumulator = Accumulator::new().channel_map(map);
+    for e in NsclData::new(&data) {
+        accumulator.observe(&e.unwrap()).unwrap();
+    }
+    let totals: Vec<_> = accumulator
+        .named_totals()
+        .map(|(label, total)| (label.to_string(), total))
+        .collect();
+    assert_eq!(
+        totals,
+        vec![
+            ("clock".to_string(), 1),
+            ("live trigger".to_string(), 2),
+            ("not # a comment".to_string(), 3),
+            ("beam".to_string(), 4),
+            ("scint_left".to_string(), 5),
+        ]
+    );
+    assert_eq!(accumulator.total_by_name("beam"), Some(4));
+
+    // Scalers without names are labeled by source id and index
+    let accumulator = {
+        let mut a = Accumulator::new();
+        for e in NsclData::new(&data) {
+            a.observe(&e.unwrap()).unwrap();
+        }
+        a
+    };
+    let labels: Vec<_> = accumulator.named_totals().map(|x| x.0).collect();
+    assert_eq!(labels[0], Label::Index(7, 0));
+    assert_eq!(labels[4].to_string(), "8:1");
+}
@@ -0,0 +1,146 @@
+use nscl_evt::{
+    writer::{
+        PeriodicScalersBuilder, PhysicsEventCountBuilder, RawBuilder, RingFormatBuilder,
+        StateChangeBuilder, TextBuilder,
+    },
+    FormatVersion, NsclData, RingItemWriter, RunClock,
+};
+use std::time::{Duration, UNIX_EPOCH};
+
+fn run(version: FormatVersion) -> Vec<u8> {
+    let mut w = RingItemWriter::with_version(Vec::new(), version);
+    if version != FormatVersion::V10 {
+        w.write(&RingFormatBuilder::new(version)).unwrap();
+    }
+    w.write(
+        &StateChangeBuilder::begin_run(1)
+            .time_offset(0, 1)
+            .timestamp(1_000_000),
+    )
+    .unwrap();
+    w.write(&PeriodicScalersBuilder::new(vec![1]).interval(3, 7, 2))
+        .unwrap();
+    w.write(&PhysicsEventCountBuilder::new(5).time_offset(5, 0))
+        .unwrap();
+    w.write(&TextBuilder::monitored_variables().time_offset(6, 1))
+        .unwrap();
+    w.write(&RawBuilder::physics_event(vec![])).unwrap();
+    w.write(
+        &StateChangeBuilder::end_run(1)
+            .time_offset(9, 1)
+            .timestamp(1_000_009),
+    )
+    .unwrap();
+    w.into_inner()
+}
+
+#[test]
+fn offsets() {
+    let data = run(FormatVersion::V11);
+    let items: Vec<_> = NsclData::new(&data).skip(1).map(Result::unwrap).collect();
+
+    let ri = items[0].ring_item().unwrap();
+    let begin_run = ri.as_begin_run().unwrap();
+    assert_eq!(begin_run.elapsed().unwrap(), Duration::ZERO);
+    assert_eq!(
+        begin_run.system_time().unwrap(),
+        UNIX_EPOCH + Duration::from_secs(1_000_000)
+    );
+
+    let ri = items[1].ring_item().unwrap();
+    let scalers = ri.as_periodic_scalers().unwrap();
+    assert_eq!(
+        scalers.interval_start().unwrap(),
+        Duration::from_millis(1500)
+    );
+    assert_eq!(scalers.interval_end().unwrap(), Duration::from_millis(3500));
+    // Scalers are placed at the end of their interval
+    assert_eq!(ri.elapsed().unwrap(), Some(Duration::from_millis(3500)));
+
+    // A divisor of 0 is treated as 1
+    let ri = items[2].ring_item().unwrap();
+    assert_eq!(ri.elapsed().unwrap(), Some(Duration::from_secs(5)));
+
+    let ri = items[3].ring_item().unwrap();
+    assert_eq!(ri.elapsed().unwrap(), Some(Duration::from_secs(6)));
+
+    let ri = items[4].ring_item().unwrap();
+    assert_eq!(ri.elapsed().unwrap(), None);
+}
+
+#[test]
+fn run_clock() {
+    let data = run(FormatVersion::V11);
+    let mut clock = RunClock::new();
+    let mut times = Vec::new();
+    for e in NsclData::new(&data) {
+        let e = e.unwrap();
+        clock.observe(&e).unwrap();
+        times.push(clock.time(&e).unwrap());
+    }
+    let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+    assert_eq!(clock.start(), Some(start));
+    assert_eq!(
+        times,
+        vec![
+            None,
+            Some(start),
+            Some(start + Duration::from_millis(3500)),
+            Some(start + Duration::from_secs(5)),
+            Some(start + Duration::from_secs(6)),
+            None,
+            Some(start + Duration::from_secs(9)),
+        ]
+    );
+}
+
+#[test]
+fn run_clock_v10() {
+    let data = run(FormatVersion::V10);
+    let mut clock = RunClock::new();
+    for e in NsclData::new(&data) {
+        clock.observe(&e.unwrap()).unwrap();
+    }
+    assert_eq!(
+        clock.start(),
+        Some(UNIX_EPOCH + Duration::from_secs(1_000_000))
+    );
+}
+
+#[test]
+fn clock_before_begin_run() {
+    let data = run(FormatVersion::V11);
+    let clock = RunClock::new();
+    for e in NsclData::new(&data) {
+        assert_eq!(clock.time(&e.unwrap()).unwrap(), None);
+    }
+}
+
+#[test]
+fn late_begin_run() {
+    // The begin run item was written 2.5s into the run
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    w.write(
+        &StateChangeBuilder::begin_run(1)
+            .time_offset(5, 2)
+            .timestamp(1_000_000),
+    )
+    .unwrap();
+    w.write(&StateChangeBuilder::end_run(1).time_offset(10, 1))
+        .unwrap();
+    let data = w.into_inner();
+
+    let mut clock = RunClock::new();
+    let times: Vec<_> = NsclData::new(&data)
+        .map(|e| {
+            let e = e.unwrap();
+            clock.observe(&e).unwrap();
+            clock.time(&e).unwrap()
+        })
+        .collect();
+    let start = UNIX_EPOCH + Duration::from_millis(999_997_500);
+    assert_eq!(clock.start(), Some(start));
+    assert_eq!(times[2], Some(start + Duration::from_secs(10)));
+}
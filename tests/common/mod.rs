@@ -0,0 +1,61 @@
+//! Helpers for building raw data, for cases that the writer can't produce.
+#![allow(dead_code)]
+
+use nscl_evt::{
+    writer::{BodyHeaderBuilder, Encode, RawBuilder},
+    FormatVersion, OwnedEvent,
+};
+
+/// A ring item with the given type, body header and body.
+pub fn item(type_id: u32, body_header: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend(((8 + body_header.len() + body.len()) as u32).to_le_bytes());
+    v.extend(type_id.to_le_bytes());
+    v.extend(body_header);
+    v.extend(body);
+    v
+}
+
+/// An empty 11.x body header.
+pub const NO_BODY_HEADER: [u8; 4] = [0; 4];
+
+/// An empty 12.x body header, which counts its own size.
+pub const EMPTY_BODY_HEADER: [u8; 4] = [4, 0, 0, 0];
+
+/// The little-endian bytes of some 32-bit words.
+pub fn words32(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// The little-endian bytes of some 16-bit words.
+pub fn words16(words: &[u16]) -> Vec<u8> {
+    words.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// An event builder fragment with the given header fields and payload.
+pub fn fragment(timestamp: u64, source_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut v = timestamp.to_le_bytes().to_vec();
+    v.extend(source_id.to_le_bytes());
+    v.extend((payload.len() as u32).to_le_bytes());
+    v.extend(0u32.to_le_bytes());
+    v.extend(payload);
+    v
+}
+
+/// The body of a built event, which starts with its size in bytes.
+pub fn built(fragments: &[Vec<u8>]) -> Vec<u8> {
+    let body = fragments.concat();
+    let mut v = ((body.len() + 4) as u32).to_le_bytes().to_vec();
+    v.extend(body);
+    v
+}
+
+/// An 11.x physics event with a full body header.
+pub fn physics_event(timestamp: u64, source_id: u32, body: Vec<u8>) -> OwnedEvent {
+    RawBuilder::physics_event(body)
+        .to_event(
+            Some(&BodyHeaderBuilder::new(timestamp, source_id)),
+            FormatVersion::V11,
+        )
+        .unwrap()
+}
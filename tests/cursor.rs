@@ -0,0 +1,45 @@
+use nscl_evt::{
+    writer::{Encode, RawBuilder},
+    Cursor, FormatVersion, ParseError,
+};
+
+#[test]
+fn word_views() {
+    let e = RawBuilder::physics_event(vec![4, 0, 0, 0, 1, 0, 2, 0, 9])
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(ri.body_size().unwrap(), 4);
+    assert_eq!(ri.words16().collect::<Vec<_>>(), vec![4, 0, 1, 2]);
+    assert_eq!(ri.words32().collect::<Vec<_>>(), vec![4, 0x0002_0001]);
+}
+
+#[test]
+fn reads() {
+    let bytes = [1, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 9];
+    let mut c = Cursor::new(&bytes);
+    assert_eq!(c.peek_u16().unwrap(), 1);
+    assert_eq!(c.read_u16().unwrap(), 1);
+    assert_eq!(c.read_u32().unwrap(), 2);
+    assert_eq!(c.read_u64().unwrap(), 3);
+    assert_eq!(c.position(), 14);
+    assert_eq!(c.remaining(), 1);
+    assert_eq!(c.rest(), &[9]);
+    c.skip(1).unwrap();
+    assert!(c.is_empty());
+}
+
+#[test]
+fn reads_past_the_end() {
+    let bytes = [1, 0, 2];
+    let mut c = Cursor::new(&bytes);
+    c.skip(2).unwrap();
+    assert_eq!(
+        c.read_u32(),
+        Err(ParseError::OutOfBounds { offset: 2, len: 4 })
+    );
+    assert!(c.read_bytes(2).is_err());
+    // A failed read doesn't move the cursor
+    assert_eq!(c.position(), 2);
+    assert_eq!(c.read_bytes(1).unwrap(), &[2]);
+}
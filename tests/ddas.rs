@@ -0,0 +1,123 @@
+mod common;
+
+use common::{built, fragment, words32};
+use nscl_evt::{
+    ddas::{self, Error, Hit},
+    writer::{BodyHeaderBuilder, Encode, RawBuilder},
+    FormatVersion, ParseError,
+};
+
+/// A DDAS body for a 250 MHz Rev. F module in crate 1, slot 2, channel 3, with the given header
+/// words after the first, and a trace.
+fn body(header: &[u32], trace: &[u16]) -> Vec<u8> {
+    let header_length = 1 + header.len() as u32;
+    let event_length = header_length + trace.len() as u32 / 2;
+    let mut words = vec![
+        250 | (14 << 16) | (0xf << 24),
+        3 | (2 << 4) | (1 << 8) | (header_length << 12) | (event_length << 17),
+    ];
+    words.extend(header);
+    let mut v = words32(&[0]);
+    v.extend(words32(&words));
+    v.extend(trace.iter().flat_map(|x| x.to_le_bytes()));
+    let size = (v.len() / 2) as u32;
+    v[..4].copy_from_slice(&size.to_le_bytes());
+    v
+}
+
+/// The timestamp, CFD and energy words, for an energy of 1000 and a trace of `trace_length`.
+fn basic_header(trace_length: u32) -> Vec<u32> {
+    vec![
+        100,
+        5 | (8192 << 16) | (1 << 30),
+        1000 | (trace_length << 16),
+    ]
+}
+
+#[test]
+fn hit_with_trace() {
+    let mut header = basic_header(4);
+    header.extend([77, 1]);
+    let hit = Hit::decode(&body(&header, &[1, 2, 3, 4])).unwrap();
+    assert_eq!(hit.module.adc_frequency, 250);
+    assert_eq!(hit.module.adc_bits, 14);
+    assert_eq!(hit.module.revision, 0xf);
+    assert_eq!((hit.crate_id, hit.slot, hit.channel), (1, 2, 3));
+    assert_eq!((hit.header_length, hit.event_length), (6, 8));
+    assert!(!hit.finish_code);
+    assert_eq!(hit.timestamp, (5 << 32) | 100);
+    assert_eq!((hit.cfd_fraction, hit.cfd_trigger_source), (8192, 1));
+    assert!(!hit.cfd_fail);
+    assert_eq!(hit.energy, 1000);
+    assert_eq!(hit.energy_sums, None);
+    assert_eq!(hit.qdc_sums, None);
+    assert_eq!(hit.external_timestamp, Some((1 << 32) | 77));
+    assert_eq!(hit.trace, vec![1, 2, 3, 4]);
+    assert_eq!(hit.coarse_time(), hit.timestamp as f64 * 8.0);
+    assert_eq!(hit.time(), hit.coarse_time() - 2.0);
+}
+
+#[test]
+fn sums() {
+    let mut header = basic_header(0);
+    header.extend(1..=4);
+    header.extend(11..=18);
+    header.extend([77, 1]);
+    let hit = Hit::decode(&body(&header, &[])).unwrap();
+    assert_eq!(hit.header_length, 18);
+    assert_eq!(hit.energy_sums, Some([1, 2, 3, 4]));
+    assert_eq!(hit.qdc_sums, Some([11, 12, 13, 14, 15, 16, 17, 18]));
+    assert_eq!(hit.external_timestamp, Some((1 << 32) | 77));
+    assert!(hit.trace.is_empty());
+}
+
+#[test]
+fn errors() {
+    let mut header = basic_header(0);
+    header.push(0);
+    assert_eq!(
+        Hit::decode(&body(&header, &[])),
+        Err(Error::BadHeaderLength(5))
+    );
+    assert_eq!(
+        Hit::decode(&body(&basic_header(6), &[1, 2, 3, 4])),
+        Err(Error::BadTraceLength {
+            trace_length: 6,
+            words: 2
+        })
+    );
+    let mut data = body(&basic_header(0), &[]);
+    data[4] = 200;
+    assert_eq!(Hit::decode(&data), Err(Error::UnknownAdcFrequency(200)));
+    let data = body(&basic_header(4), &[1, 2, 3, 4]);
+    assert!(matches!(
+        Hit::decode(&data[..data.len() - 2]),
+        Err(Error::Parse(ParseError::OutOfBounds { .. }))
+    ));
+}
+
+#[test]
+fn hits_in_built_event() {
+    let payloads: Vec<_> = (0..2)
+        .map(|i| {
+            RawBuilder::physics_event(body(&basic_header(2), &[i, i]))
+                .to_event(
+                    Some(&BodyHeaderBuilder::new(100, i as u32)),
+                    FormatVersion::V11,
+                )
+                .unwrap()
+        })
+        .collect();
+    let e = RawBuilder::physics_event(built(&[
+        fragment(100, 0, payloads[0].bytes()),
+        fragment(100, 1, payloads[1].bytes()),
+    ]))
+    .to_event(Some(&BodyHeaderBuilder::new(100, 10)), FormatVersion::V11)
+    .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let hits = ddas::hits(&ri).unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[1].trace, vec![1, 1]);
+    let ri = payloads[0].ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(Hit::from_physics_event(&ri).unwrap(), hits[0]);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::{built, fragment, words16, words32};
+use nscl_evt::{
+    caen, ddas,
+    decode::{Caen, Ddas, Mesytec, Registry, V1190},
+    mesytec, v1190,
+    writer::{BodyHeaderBuilder, Encode, RawBuilder, RingFormatBuilder, TextBuilder},
+    FormatVersion, NsclData, RingItemWriter,
+};
+
+type Error = Box<dyn std::error::Error>;
+type Found = Vec<(u32, Vec<u8>)>;
+
+/// Write a RingFormat item, then a built event with a fragment for each body.
+fn built_event(bodies: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let fragments: Vec<_> = bodies
+        .iter()
+        .map(|(source_id, body)| {
+            let e = RawBuilder::physics_event(body.clone())
+                .to_event(
+                    Some(&BodyHeaderBuilder::new(10, *source_id)),
+                    FormatVersion::V11,
+                )
+                .unwrap();
+            fragment(10, *source_id, e.bytes())
+        })
+        .collect();
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    w.write(&TextBuilder::packet_types()).unwrap();
+    w.write_with_body_header(
+        &BodyHeaderBuilder::new(10, 99),
+        &RawBuilder::physics_event(built(&fragments)),
+    )
+    .unwrap();
+    w.into_inner()
+}
+
+/// A body of 16-bit words, after a 32-bit size.
+fn sized(words: &[u16]) -> Vec<u8> {
+    let mut v = (2 + words.len() as u32).to_le_bytes().to_vec();
+    v.extend(words16(words));
+    v
+}
+
+fn recorder(id: u32) -> impl FnMut(&[u8], &mut Found) -> Result<(), Error> {
+    move |data, output| {
+        output.push((id, data.to_vec()));
+        Ok(())
+    }
+}
+
+#[test]
+fn dispatch() {
+    let data = built_event(&[
+        (1, vec![1, 2, 3, 4]),
+        (2, sized(&[4, 0x55, 9, 8, 3, 0x56, 7])),
+    ]);
+    let mut registry: Registry<Found, Error> = Registry::new()
+        .source_id(1, recorder(1))
+        .packet_tag(0x55, recorder(0x55));
+    let outputs: Vec<_> = registry
+        .decode_events(NsclData::new(&data))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        outputs,
+        vec![vec![(1, vec![1, 2, 3, 4]), (0x55, vec![9, 0, 8, 0])]]
+    );
+}
+
+#[test]
+fn leaves_without_packets_are_skipped() {
+    // The second leaf's body isn't made of packets, and its source id has no decoder
+    let data = built_event(&[
+        (2, sized(&[3, 0x55, 9])),
+        (3, words32(&[0xffff_ffff, 0x1234_5678])),
+    ]);
+    let mut registry: Registry<Found, Error> = Registry::new().packet_tag(0x55, recorder(0x55));
+    let outputs: Vec<_> = registry
+        .decode_events(NsclData::new(&data))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(outputs, vec![vec![(0x55, vec![9, 0])]]);
+}
+
+#[test]
+fn decoder_errors() {
+    let data = built_event(&[(1, vec![1, 2, 3, 4])]);
+    let mut registry: Registry<Found, Error> = Registry::new()
+        .source_id(1, |_: &[u8], _: &mut Found| -> Result<(), Error> {
+            Err("bad".into())
+        });
+    let results: Vec<_> = registry.decode_events(NsclData::new(&data)).collect();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap_err().to_string(), "bad");
+}
+
+#[derive(Debug, Default)]
+struct Modules {
+    hits: Vec<ddas::Hit>,
+    caen: Vec<caen::Block>,
+    mesytec: Vec<mesytec::Block>,
+    v1190: Vec<v1190::Event>,
+}
+
+impl Extend<ddas::Hit> for Modules {
+    fn extend<I: IntoIterator<Item = ddas::Hit>>(&mut self, iter: I) {
+        self.hits.extend(iter);
+    }
+}
+
+impl Extend<caen::Block> for Modules {
+    fn extend<I: IntoIterator<Item = caen::Block>>(&mut self, iter: I) {
+        self.caen.extend(iter);
+    }
+}
+
+impl Extend<mesytec::Block> for Modules {
+    fn extend<I: IntoIterator<Item = mesytec::Block>>(&mut self, iter: I) {
+        self.mesytec.extend(iter);
+    }
+}
+
+impl Extend<v1190::Event> for Modules {
+    fn extend<I: IntoIterator<Item = v1190::Event>>(&mut self, iter: I) {
+        self.v1190.extend(iter);
+    }
+}
+
+fn sized32(words: &[u32]) -> Vec<u8> {
+    let mut v = (2 + 2 * words.len() as u32).to_le_bytes().to_vec();
+    v.extend(words32(words));
+    v
+}
+
+#[test]
+fn module_decoders() {
+    let ddas = {
+        let words = [
+            250 | (14 << 16) | (0xf << 24),
+            3 | (4 << 12) | (4 << 17),
+            100,
+            5,
+            1000,
+        ];
+        sized32(&words)
+    };
+    let caen = sized32(&[
+        (3 << 27) | (2 << 24) | (1 << 8),
+        (3 << 27) | (1 << 16) | 100,
+        (3 << 27) | (4 << 24) | 42,
+    ]);
+    let madc = words32(&[0x4000_0000 | (7 << 16) | 2, 0x0400_0000 | 123, 0xc000_0001]);
+    let mut packet = words16(&[2 + madc.len() as u16 / 2, 0x55]);
+    packet.extend(madc);
+    let mut mesytec = (2 + packet.len() as u32 / 2).to_le_bytes().to_vec();
+    mesytec.extend(packet);
+    let v1190 = sized32(&[
+        (0b01000 << 27) | (17 << 5) | 4,
+        (0b10000 << 27) | (2 << 5) | 4,
+    ]);
+
+    let data = built_event(&[(1, ddas), (2, caen), (3, mesytec), (4, v1190)]);
+    let mut registry: Registry<Modules, Error> = Registry::new()
+        .source_id(1, Ddas)
+        .source_id(2, Caen::new())
+        .packet_tag(0x55, Mesytec::without_size(mesytec::Module::Madc32))
+        .source_id(4, V1190::new(v1190::Model::V1190));
+    let outputs: Vec<_> = registry
+        .decode_events(NsclData::new(&data))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(outputs.len(), 1);
+    let output = &outputs[0];
+    assert_eq!(output.hits.len(), 1);
+    assert_eq!(output.hits[0].energy, 1000);
+    assert_eq!(output.caen.len(), 1);
+    assert_eq!(output.caen[0].event_counter, 42);
+    assert_eq!(output.mesytec.len(), 1);
+    assert_eq!(output.mesytec[0].channel(0).unwrap().value, 123);
+    assert_eq!(output.v1190.len(), 1);
+    assert_eq!(output.v1190[0].event_count, 17);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{item, words16, words32, NO_BODY_HEADER};
+use nscl_evt::{
+    writer::{BodyHeaderBuilder, RawBuilder, RingFormatBuilder, StateChangeBuilder},
+    FormatVersion, NsclData, NsclReader, RingItemWriter,
+};
+
+/// A 10.x run: a begin run item, then physics events from a readout program, which start with
+/// their size in 16-bit words.
+fn ten_run(title: &str) -> Vec<u8> {
+    let mut body = words32(&[50, 0, 1_600_000_000]);
+    body.extend(title.as_bytes());
+    body.resize(12 + 80, 0);
+    let mut data = item(1, &[], &body);
+    for n in 0..4u16 {
+        data.extend(item(
+            30,
+            &[],
+            &words16(&[12, 0, n, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        ));
+    }
+    data
+}
+
+#[test]
+fn ten() {
+    for title in ["test run", ""] {
+        let data = ten_run(title);
+        let d = NsclData::new(&data);
+        assert_eq!(d.version(), FormatVersion::V10);
+        let events: Vec<_> = d.map(Result::unwrap).collect();
+        assert_eq!(events.len(), 5);
+        let ri = events[0].ring_item().unwrap().as_begin_run().unwrap();
+        assert_eq!(ri.run_number().unwrap(), 50);
+        assert_eq!(ri.title().unwrap(), title);
+        let ri = events[1].ring_item().unwrap().as_physics_event().unwrap();
+        assert_eq!(ri.body_size().unwrap(), 12);
+    }
+}
+
+#[test]
+fn ring_format() {
+    for version in [FormatVersion::V11, FormatVersion::V12] {
+        let mut w = RingItemWriter::with_version(Vec::new(), version);
+        w.write(&RingFormatBuilder::new(version)).unwrap();
+        w.write(&StateChangeBuilder::begin_run(50)).unwrap();
+        w.write_with_body_header(
+            &BodyHeaderBuilder::new(100, 1),
+            &RawBuilder::physics_event(vec![1, 2, 3, 4]),
+        )
+        .unwrap();
+        let data = w.into_inner();
+        assert_eq!(NsclData::new(&data).version(), version);
+    }
+}
+
+#[test]
+fn empty_body_headers() {
+    let mut data = item(30, &NO_BODY_HEADER, &[1, 2]);
+    data.extend(item(30, &NO_BODY_HEADER, &[3, 4]));
+    assert_eq!(NsclData::new(&data).version(), FormatVersion::V11);
+    let data = item(30, &4u32.to_le_bytes(), &[1, 2]);
+    assert_eq!(NsclData::new(&data).version(), FormatVersion::V12);
+}
+
+/// A segment of an 11.x run without its RingFormat item, e.g. from filtered output.
+fn eleven_segment() -> Vec<u8> {
+    let mut w = RingItemWriter::new(Vec::new());
+    for n in 0..4u8 {
+        w.write_with_body_header(
+            &BodyHeaderBuilder::new(100 + n as u64, 1),
+            &RawBuilder::physics_event(vec![n; 6]),
+        )
+        .unwrap();
+    }
+    w.write(&StateChangeBuilder::end_run(50)).unwrap();
+    w.into_inner()
+}
+
+#[test]
+fn full_body_headers_without_ring_format() {
+    let data = eleven_segment();
+    let d = NsclData::new(&data);
+    assert_eq!(d.version(), FormatVersion::V11);
+    let events: Vec<_> = d.map(Result::unwrap).collect();
+    assert_eq!(events[3].body_header().unwrap().timestamp(), Some(103));
+    let ri = events[3].ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(ri.bytes(), &[3; 6]);
+    assert_eq!(
+        events[4]
+            .ring_item()
+            .unwrap()
+            .as_end_run()
+            .unwrap()
+            .run_number()
+            .unwrap(),
+        50
+    );
+
+    // A single item is too little to go on, so it's read as 11.x too
+    let size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    assert_eq!(NsclData::new(&data[..size]).version(), FormatVersion::V11);
+}
+
+#[test]
+fn varying_sizes() {
+    // Each body's size could be a body header size on its own, but they don't agree
+    let mut data = Vec::new();
+    for words in [24u16, 30] {
+        let mut body = words16(&[words, 0]);
+        body.resize(words as usize * 2, 0);
+        data.extend(item(30, &[], &body));
+    }
+    assert_eq!(NsclData::new(&data).version(), FormatVersion::V10);
+}
+
+#[test]
+fn implausible_body_header_size() {
+    let mut data = item(30, &NO_BODY_HEADER, &[1, 2]);
+    data.extend(item(30, &[], &words32(&[100, 1])));
+    assert_eq!(NsclData::new(&data).version(), FormatVersion::V10);
+}
+
+#[test]
+fn reader() {
+    let data = ten_run("test run");
+    let mut r = NsclReader::new(&data[..]);
+    let e = r.next_event().unwrap().unwrap();
+    let ri = e.ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 50);
+    assert_eq!(r.version(), Some(FormatVersion::V10));
+}
+
+#[test]
+fn reader_reads_ahead() {
+    let data = eleven_segment();
+    let mut r = NsclReader::new(&data[..]);
+    let mut timestamps = Vec::new();
+    r.for_each_event(|e| timestamps.push(e.body_header().unwrap().timestamp()))
+        .unwrap();
+    assert_eq!(r.version(), Some(FormatVersion::V11));
+    assert_eq!(
+        timestamps,
+        vec![Some(100), Some(101), Some(102), Some(103), None]
+    );
+}
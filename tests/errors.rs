@@ -0,0 +1,73 @@
+mod common;
+
+use common::{item, words32, NO_BODY_HEADER};
+use nscl_evt::{Event, NsclData, ParseError};
+
+#[test]
+fn truncated_item() {
+    let mut data = item(30, &NO_BODY_HEADER, &[1, 2, 3, 4]);
+    data.truncate(14);
+    assert_eq!(
+        Event::new(&data).unwrap_err(),
+        ParseError::TruncatedItem {
+            size: 16,
+            available: 14
+        }
+    );
+}
+
+#[test]
+fn zero_size() {
+    let data = words32(&[0, 30, 0]);
+    assert_eq!(Event::new(&data).unwrap_err(), ParseError::ZeroSize);
+}
+
+#[test]
+fn bad_body_header_size() {
+    let data = item(30, &8u32.to_le_bytes(), &[0; 8]);
+    let event = Event::new(&data).unwrap();
+    assert_eq!(
+        event.body_header().unwrap_err(),
+        ParseError::BadBodyHeaderSize(8)
+    );
+}
+
+#[test]
+fn field_out_of_bounds() {
+    // A begin run item with no room for its title
+    let data = item(1, &NO_BODY_HEADER, &words32(&[7, 0, 0, 0]));
+    let event = Event::new(&data).unwrap();
+    let ri = event.ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 7);
+    assert!(matches!(
+        ri.title(),
+        Err(ParseError::OutOfBounds { offset: 16, .. })
+    ));
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut body = words32(&[7, 0, 0, 0]);
+    body.extend([0xff; 80]);
+    let data = item(1, &NO_BODY_HEADER, &body);
+    let event = Event::new(&data).unwrap();
+    let ri = event.ring_item().unwrap().as_begin_run().unwrap();
+    assert!(matches!(ri.title(), Err(ParseError::InvalidUtf8(_))));
+}
+
+#[test]
+fn iteration_stops_after_an_error() {
+    let mut data = item(30, &NO_BODY_HEADER, &[1, 2]);
+    data.extend(words32(&[100, 30]));
+    data.extend(item(30, &NO_BODY_HEADER, &[3, 4]));
+    let results: Vec<_> = NsclData::new(&data).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].unwrap_err(),
+        ParseError::TruncatedItem {
+            size: 100,
+            available: 22
+        }
+    );
+}
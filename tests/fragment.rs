@@ -0,0 +1,50 @@
+mod common;
+
+use common::{built, fragment, physics_event};
+use nscl_evt::ParseError;
+
+#[test]
+fn fragments() {
+    let a = physics_event(10, 1, vec![1, 2, 3, 4]);
+    let b = physics_event(11, 2, vec![5, 6]);
+    let e = physics_event(
+        10,
+        99,
+        built(&[fragment(10, 1, a.bytes()), fragment(11, 2, b.bytes())]),
+    );
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let fragments: Vec<_> = ri.fragments().unwrap().map(Result::unwrap).collect();
+    assert_eq!(fragments.len(), 2);
+    assert_eq!(fragments[0].timestamp(), 10);
+    assert_eq!(fragments[1].source_id(), 2);
+    assert_eq!(fragments[1].payload_size() as usize, b.bytes().len());
+    assert_eq!(fragments[1].barrier_type(), 0);
+    let inner = fragments[1].event().unwrap();
+    assert_eq!(inner.body_header().unwrap().source_id(), Some(2));
+    assert_eq!(inner.ring_item().unwrap().bytes(), &[5, 6]);
+}
+
+#[test]
+fn truncated_fragment() {
+    let a = physics_event(10, 1, vec![1, 2, 3, 4]);
+    let mut body = built(&[fragment(10, 1, a.bytes()), fragment(11, 2, a.bytes())]);
+    // Claim a longer payload for the second fragment
+    let size_offset = 4 + 20 + a.bytes().len() + 12;
+    body[size_offset] += 4;
+    let e = physics_event(10, 99, body);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let results: Vec<_> = ri.fragments().unwrap().collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(ParseError::TruncatedItem { .. })));
+}
+
+#[test]
+fn bad_size() {
+    let e = physics_event(10, 99, vec![100, 0, 0, 0]);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert!(ri.fragments().is_err());
+    let e = physics_event(10, 99, vec![0, 0, 0, 0]);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(ri.fragments().unwrap_err(), ParseError::ZeroSize);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{words16, words32};
+use nscl_evt::{
+    legacy::{Buffers, DEFAULT_BUFFER_SIZE},
+    FormatVersion, NsclData, ParseError,
+};
+
+/// An 8.x buffer of run 42 with the given type, entity count and body.
+fn buffer(buffer_type: u16, entities: u16, body: &[u8]) -> Vec<u8> {
+    let words = ((32 + body.len()) / 2) as u16;
+    let mut v = words16(&[words, buffer_type, 0, 42, 0, 0, entities]);
+    v.resize(32, 0);
+    v.extend(body);
+    v.resize(DEFAULT_BUFFER_SIZE, 0);
+    v
+}
+
+#[test]
+fn conversion() {
+    let mut body = b"old title".to_vec();
+    body.resize(80, 0);
+    body.extend(5u32.to_le_bytes());
+    body.extend(words16(&[3, 15, 2004, 12, 30, 0, 0]));
+    let mut data = buffer(11, 0, &body);
+    data.extend(buffer(1, 2, &words16(&[3, 7, 8, 2, 9])));
+    let mut body = words32(&[10]);
+    body.extend([0; 6]);
+    body.extend(0u32.to_le_bytes());
+    body.extend([0; 6]);
+    body.extend(words32(&[11, 12]));
+    data.extend(buffer(2, 2, &body));
+    data.extend(buffer(5, 2, b"set a 1\0set bb 2\0\0"));
+
+    let buffers: Vec<_> = Buffers::new(&data).map(Result::unwrap).collect();
+    assert_eq!(buffers.len(), 4);
+    assert_eq!(buffers[1].run_number(), 42);
+    assert_eq!(buffers[1].entity_count(), 2);
+
+    let items = Buffers::new(&data).to_ring_items().unwrap();
+    let d = NsclData::new(&items);
+    assert_eq!(d.version(), FormatVersion::V11);
+    let events: Vec<_> = d.map(Result::unwrap).collect();
+    assert_eq!(events.len(), 5);
+
+    let ri = events[0].ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.title().unwrap(), "old title");
+    assert_eq!(ri.bytes().len(), 16 + 81);
+    assert_eq!(ri.run_number().unwrap(), 42);
+    assert_eq!(ri.time_offset().unwrap(), 5);
+    assert_eq!(ri.timestamp().unwrap(), 1_079_353_800);
+
+    let ri = events[1].ring_item().unwrap();
+    assert_eq!(ri.bytes(), &[3, 0, 7, 0, 8, 0]);
+    let ri = events[2].ring_item().unwrap();
+    assert_eq!(ri.bytes(), &[2, 0, 9, 0]);
+
+    let ri = events[3]
+        .ring_item()
+        .unwrap()
+        .as_periodic_scalers()
+        .unwrap();
+    assert_eq!(ri.interval_end_offset().unwrap(), 10);
+    assert!(ri.is_incremental().unwrap());
+    assert_eq!(ri.scalers().unwrap(), vec![11, 12]);
+
+    let ri = events[4]
+        .ring_item()
+        .unwrap()
+        .as_monitored_variables()
+        .unwrap();
+    assert_eq!(ri.strings().unwrap(), vec!["set a 1", "set bb 2"]);
+}
+
+#[test]
+fn bad_buffers() {
+    let mut data = buffer(1, 0, &[]);
+    // A word count too small for the header
+    data.extend(buffer(1, 0, &[]));
+    data[DEFAULT_BUFFER_SIZE] = 2;
+    data.extend(buffer(1, 0, &[]));
+    data.extend([0; 100]);
+    let results: Vec<_> = Buffers::new(&data).collect();
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    assert!(results[2].is_ok());
+    assert_eq!(
+        results[3].unwrap_err(),
+        ParseError::TruncatedItem {
+            size: DEFAULT_BUFFER_SIZE,
+            available: 100
+        }
+    );
+}
+
+#[test]
+fn buffer_size_too_small() {
+    let data = buffer(1, 0, &[]);
+    for size in [0, 31] {
+        let results: Vec<_> = Buffers::with_buffer_size(&data, size).collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+    let results: Vec<_> = Buffers::with_buffer_size(&data, 32).collect();
+    assert_eq!(results.len(), DEFAULT_BUFFER_SIZE / 32);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::words32;
+use nscl_evt::mesytec::{Blocks, Error, Module};
+
+fn header(module_id: u32, resolution: u32, count: u32) -> u32 {
+    0x4000_0000 | (module_id << 16) | (resolution << 12) | count
+}
+
+fn end_of_event(counter: u32) -> u32 {
+    0xc000_0000 | counter
+}
+
+fn madc_block() -> Vec<u32> {
+    vec![
+        header(7, 3, 4),
+        0x0400_0000 | (2 << 16) | (1 << 14) | 123,
+        0,
+        0x0480_0000 | 0x12,
+        end_of_event(99),
+    ]
+}
+
+#[test]
+fn madc() {
+    let mut words = madc_block();
+    words.extend([0, 0xffff_ffff]);
+    let data = words32(&words);
+    let blocks: Vec<_> = Blocks::new(&data, Module::Madc32)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(blocks.len(), 1);
+    let b = &blocks[0];
+    assert_eq!((b.module_id, b.resolution, b.counter), (7, 3, 99));
+    assert_eq!(b.extended_timestamp, Some(0x12));
+    assert_eq!(b.timestamp(), (0x12 << 30) | 99);
+    let d = b.channel(2).unwrap();
+    assert_eq!(d.value, 123);
+    assert!(d.out_of_range);
+    assert!(b.channel(3).is_none());
+}
+
+#[test]
+fn mqdc_and_mtdc() {
+    let data = words32(&[
+        header(1, 0, 2),
+        0x0400_0000 | (5 << 16) | (1 << 15) | 0xabc,
+        end_of_event(1),
+    ]);
+    let b = Blocks::new(&data, Module::Mqdc32).next().unwrap().unwrap();
+    assert_eq!(b.channel(5).unwrap().value, 0xabc);
+    assert!(b.channel(5).unwrap().out_of_range);
+    assert_eq!(b.extended_timestamp, None);
+    assert_eq!(b.timestamp(), 1);
+
+    let data = words32(&[
+        header(1, 0, 3),
+        0x0400_0000 | (5 << 16) | 0xbeef,
+        0x0420_0000 | (1 << 16) | 0x1234,
+        end_of_event(1),
+    ]);
+    let b = Blocks::new(&data, Module::Mtdc32).next().unwrap().unwrap();
+    assert_eq!(b.channel(5).unwrap().value, 0xbeef);
+    assert!(b.data[1].trigger);
+    assert_eq!(b.data[1].channel, 1);
+    // Trigger inputs aren't channels
+    assert!(b.channel(1).is_none());
+}
+
+#[test]
+fn errors() {
+    let words = madc_block();
+    assert_eq!(
+        Blocks::new(&words32(&words[..3]), Module::Madc32).next(),
+        Some(Err(Error::MissingEndOfEvent))
+    );
+    let mut bad = words.clone();
+    bad[0] = header(7, 3, 5);
+    assert_eq!(
+        Blocks::new(&words32(&bad), Module::Madc32).next(),
+        Some(Err(Error::CountMismatch {
+            header: 5,
+            words: 4
+        }))
+    );
+    let mut bad = words.clone();
+    bad[2] = 0x1234_5678;
+    assert_eq!(
+        Blocks::new(&words32(&bad), Module::Madc32).next(),
+        Some(Err(Error::UnexpectedWord(0x1234_5678)))
+    );
+}
+
+#[test]
+fn scan() {
+    let mut words = vec![0xdead_beef];
+    words.extend(madc_block());
+    words.push(header(8, 0, 9));
+    words.extend(madc_block());
+    let data = words32(&words);
+
+    let results: Vec<_> = Blocks::new(&data, Module::Madc32).collect();
+    assert_eq!(results, vec![Err(Error::UnexpectedWord(0xdead_beef))]);
+
+    let blocks: Vec<_> = Blocks::scan(&data, Module::Madc32)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks.iter().all(|x| x.module_id == 7));
+}
@@ -0,0 +1,145 @@
+use nscl_evt::{
+    monitor::{parse_set, Error, Name, Tracker, Value, Variables},
+    writer::{RingFormatBuilder, StateChangeBuilder, TextBuilder},
+    FormatVersion, NsclData, RingItemWriter,
+};
+use std::time::Duration;
+
+fn value(s: &str) -> Value {
+    parse_set(s).unwrap().1
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(value("set x 1.5"), Value::Number(1.5));
+    assert_eq!(value("set x -0x10"), Value::Number(-16.0));
+    assert_eq!(value("set x +3"), Value::Number(3.0));
+    assert_eq!(value("set x {3}"), Value::Number(3.0));
+    // Tcl accepts these as doubles, but they're more likely to be names
+    assert_eq!(value("set x nan"), Value::String("nan".into()));
+    assert_eq!(value("set x inf"), Value::String("inf".into()));
+}
+
+#[test]
+fn strings_and_lists() {
+    assert_eq!(value("set x hello"), Value::String("hello".into()));
+    assert_eq!(value("set x \"a b\\\"\""), Value::String("a b\"".into()));
+    assert_eq!(value("set x \"1 2\""), Value::String("1 2".into()));
+    assert_eq!(
+        value("set x {a {b c} d}"),
+        Value::List(vec!["a".into(), "b c".into(), "d".into()])
+    );
+    assert_eq!(value("set x {}"), Value::String("".into()));
+    assert_eq!(value("set x {a b}").to_string(), "a b");
+}
+
+#[test]
+fn names() {
+    let (name, value) = parse_set("set {arr(chan 1)} {3}").unwrap();
+    assert_eq!(name.name, "arr");
+    assert_eq!(name.index.as_deref(), Some("chan 1"));
+    assert_eq!(name.to_string(), "arr(chan 1)");
+    assert_eq!(value, Value::Number(3.0));
+    assert_eq!(Name::parse("plain").index, None);
+}
+
+#[test]
+fn bad_commands() {
+    for s in [
+        "",
+        "set",
+        "set x",
+        "set x {a",
+        "set x \"a",
+        "set x {a}b",
+        "set x 1 2",
+        "puts x",
+    ] {
+        assert_eq!(
+            parse_set(s),
+            Err(Error::BadCommand(s.to_string())),
+            "{:?}",
+            s
+        );
+    }
+}
+
+fn run() -> Vec<u8> {
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    w.write(&StateChangeBuilder::begin_run(1)).unwrap();
+    w.write(
+        &TextBuilder::monitored_variables()
+            .time_offset(5, 1)
+            .string("set a(1) 2")
+            .string("set a(2) {x y}")
+            .string("set b hi"),
+    )
+    .unwrap();
+    w.write(
+        &TextBuilder::monitored_variables()
+            .time_offset(21, 2)
+            .string("set a(1) 3"),
+    )
+    .unwrap();
+    w.into_inner()
+}
+
+#[test]
+fn variables() {
+    let data = run();
+    let text = NsclData::new(&data)
+        .map(Result::unwrap)
+        .find_map(|e| e.ring_item().unwrap().as_monitored_variables())
+        .unwrap();
+    let variables = Variables::from_text(&text).unwrap();
+    assert_eq!(variables.len(), 3);
+    assert_eq!(variables.get("a(1)"), Some(&Value::Number(2.0)));
+    assert_eq!(
+        variables.element("a", "2").and_then(Value::as_list),
+        Some(&["x".to_string(), "y".to_string()][..])
+    );
+    assert_eq!(variables.get("b").and_then(Value::as_str), Some("hi"));
+    let indices: Vec<_> = variables.array("a").map(|x| x.0).collect();
+    assert_eq!(indices, vec!["1", "2"]);
+}
+
+#[test]
+fn tracker() {
+    let data = run();
+    let mut tracker = Tracker::new();
+    let observed: Vec<_> = NsclData::new(&data)
+        .map(|e| tracker.observe(&e.unwrap()).unwrap())
+        .collect();
+    assert_eq!(observed, vec![false, false, true, true]);
+
+    let a = tracker.element("a", "1").unwrap();
+    assert_eq!(a.value, Value::Number(3.0));
+    assert_eq!(a.time_offset, 21);
+    assert_eq!(a.elapsed(), Duration::from_millis(10_500));
+    let b = tracker.get("b").unwrap();
+    assert_eq!(b.time_offset, 5);
+    assert_eq!(b.elapsed(), Duration::from_secs(5));
+    assert_eq!(tracker.iter().count(), 3);
+    assert_eq!(tracker.values().get("a(1)"), Some(&Value::Number(3.0)));
+
+    tracker.clear();
+    assert!(tracker.get("b").is_none());
+}
+
+#[test]
+fn tracker_error() {
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    w.write(&TextBuilder::monitored_variables().string("puts x"))
+        .unwrap();
+    let data = w.into_inner();
+    let mut tracker = Tracker::new();
+    let e = NsclData::new(&data).nth(1).unwrap().unwrap();
+    assert_eq!(
+        tracker.observe(&e),
+        Err(Error::BadCommand("puts x".to_string()))
+    );
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{item, words32, NO_BODY_HEADER};
+use nscl_evt::{Event, FormatVersion, OwnedEvent, ParseError};
+use std::thread;
+
+#[test]
+fn trailing_bytes_are_dropped() {
+    let mut data = item(30, &NO_BODY_HEADER, &[1, 2]);
+    data.extend([1, 2, 3]);
+    let e = OwnedEvent::new(data).unwrap();
+    assert_eq!(e.bytes().len(), 14);
+    assert_eq!(e.size(), 14);
+    assert_eq!(e.ring_item().unwrap().bytes(), &[1, 2]);
+}
+
+#[test]
+fn outlives_its_data() {
+    let data = item(30, &NO_BODY_HEADER, &[1, 2]);
+    let e = Event::new(&data).unwrap().to_owned();
+    drop(data);
+    let e = thread::spawn(move || e).join().unwrap();
+    assert_eq!(e.type_id(), 30);
+    assert_eq!(e.as_ref().ring_item().unwrap().bytes(), &[1, 2]);
+}
+
+#[test]
+fn keeps_its_version() {
+    let data = item(1, &[], &words32(&[7, 0, 0]));
+    let e = OwnedEvent::with_version(data, FormatVersion::V10).unwrap();
+    assert_eq!(e.version(), FormatVersion::V10);
+    let ri = e.ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 7);
+    assert_eq!(ri.offset_divisor().unwrap(), 1);
+}
+
+#[test]
+fn bad_data() {
+    assert_eq!(
+        OwnedEvent::new(words32(&[0, 30])).unwrap_err(),
+        ParseError::ZeroSize
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::words16;
+use nscl_evt::{
+    packet::{Error, Packet, PacketType, PacketTypes, PacketWalker, Packets},
+    writer::{Encode, RawBuilder, TextBuilder},
+    FormatVersion, OwnedEvent, ParseError,
+};
+
+fn types() -> PacketTypes {
+    let e = TextBuilder::packet_types()
+        .string("top:0x100:1.0:Top level: with colon")
+        .string("adc:257:2.0:ADCs")
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_packet_types().unwrap();
+    PacketTypes::from_text(&ri).unwrap()
+}
+
+/// A physics event with a body of 16-bit words, after a 32-bit size.
+fn physics_event(words: &[u16]) -> OwnedEvent {
+    let size = 2 + words.len() as u32;
+    let mut body = size.to_le_bytes().to_vec();
+    body.extend(words16(words));
+    RawBuilder::physics_event(body)
+        .to_event(None, FormatVersion::V11)
+        .unwrap()
+}
+
+#[test]
+fn definitions() {
+    let types = types();
+    assert_eq!(types.len(), 2);
+    let top = types.get(0x100).unwrap();
+    assert_eq!(top.name, "top");
+    assert_eq!(top.version, "1.0");
+    assert_eq!(top.description, "Top level: with colon");
+    assert_eq!(types.name(257), Some("adc"));
+    assert_eq!(types.name(258), None);
+    assert_eq!(
+        types.iter().map(|x| x.id).collect::<Vec<_>>(),
+        vec![0x100, 257]
+    );
+
+    for s in ["x:zz:1:d", "x:1:2", "x:0x10000:1:d"] {
+        assert_eq!(PacketType::parse(s), Err(Error::BadDefinition(s.into())));
+    }
+}
+
+#[test]
+fn packets() {
+    let e = physics_event(&[3, 0x55, 9, 2, 0x56, 4, 0x57, 1, 2]);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let packets: Vec<_> = Packets::from_physics_event(&ri)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(packets.len(), 3);
+    assert_eq!((packets[0].size(), packets[0].tag()), (3, 0x55));
+    assert_eq!(packets[0].body(), &[9, 0]);
+    assert_eq!(packets[0].cursor().read_u16().unwrap(), 9);
+    assert!(packets[1].body().is_empty());
+    assert_eq!(packets[2].bytes().len(), 8);
+}
+
+#[test]
+fn bad_packets() {
+    let data = words16(&[1, 0x55]);
+    assert_eq!(
+        Packet::new(&data),
+        Err(Error::BadPacketSize { tag: 0x55, size: 1 })
+    );
+    let data = words16(&[4, 0x55, 1]);
+    assert_eq!(
+        Packet::new(&data),
+        Err(Error::Parse(ParseError::TruncatedItem {
+            size: 8,
+            available: 6
+        }))
+    );
+    // An error ends the iteration
+    let data = words16(&[1, 0x55, 2, 0x56]);
+    assert_eq!(Packets::new(&data).count(), 1);
+}
+
+#[test]
+fn walker() {
+    // top { adc { 1, 2 } }, then an undocumented packet
+    let e = physics_event(&[6, 0x100, 4, 257, 1, 2, 3, 0x999, 7]);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let types = types();
+    let found = PacketWalker::new(&types).walk(&ri).unwrap();
+    let summary: Vec<_> = found
+        .iter()
+        .map(|x| (x.depth, x.name, x.packet.tag()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0, Some("top"), 0x100),
+            (1, Some("adc"), 257),
+            (0, None, 0x999)
+        ]
+    );
+    assert_eq!(found[1].packet.body(), &[1, 0, 2, 0]);
+}
+
+#[test]
+fn undocumented_contents_are_not_nested() {
+    // The body of adc parses as a packet, but its tag isn't documented
+    let e = physics_event(&[4, 257, 2, 0x999]);
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let types = types();
+    let found = PacketWalker::new(&types).walk(&ri).unwrap();
+    assert_eq!(found.len(), 1);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{item, words32, EMPTY_BODY_HEADER};
+use nscl_evt::{FormatVersion, NsclReader, ParseError, ReadError};
+use std::io::{self, Read};
+
+fn data() -> Vec<u8> {
+    let mut data = item(12, &EMPTY_BODY_HEADER, &[12, 0, 0, 0]);
+    data.extend(item(30, &EMPTY_BODY_HEADER, &[1, 2]));
+    data.extend(item(30, &EMPTY_BODY_HEADER, &[3]));
+    data
+}
+
+/// A reader that returns one byte at a time.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match (self.0.split_first(), buf.first_mut()) {
+            (Some((x, rest)), Some(b)) => {
+                *b = *x;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn events() {
+    let data = data();
+    let mut r = NsclReader::new(Trickle(&data));
+    assert_eq!(r.version(), None);
+    let mut bodies = Vec::new();
+    r.for_each_event(|e| bodies.push(e.ring_item().unwrap().bytes().to_vec()))
+        .unwrap();
+    assert_eq!(bodies, vec![vec![12, 0, 0, 0], vec![1, 2], vec![3]]);
+    assert_eq!(r.version(), Some(FormatVersion::V12));
+}
+
+#[test]
+fn truncated() {
+    let mut data = data();
+    data.extend(words32(&[20, 30]));
+    let mut r = NsclReader::new(&data[..]);
+    for _ in 0..3 {
+        r.next_event().unwrap().unwrap();
+    }
+    match r.next_event() {
+        Some(Err(ReadError::Parse(e))) => assert_eq!(
+            e,
+            ParseError::TruncatedItem {
+                size: 20,
+                available: 8
+            }
+        ),
+        x => panic!("expected a truncated item, got {:?}", x.map(|x| x.is_ok())),
+    }
+    assert!(r.next_event().is_none());
+}
+
+#[test]
+fn zero_size() {
+    let mut data = data();
+    data.extend(words32(&[0, 30]));
+    let mut r = NsclReader::with_version(&data[..], FormatVersion::V11);
+    assert_eq!(r.version(), Some(FormatVersion::V11));
+    assert!(matches!(
+        r.for_each_event(|_| {}),
+        Err(ReadError::Parse(ParseError::ZeroSize))
+    ));
+    assert_eq!(r.version(), Some(FormatVersion::V12));
+}
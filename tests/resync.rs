@@ -0,0 +1,89 @@
+mod common;
+
+use common::{item, words32, NO_BODY_HEADER};
+use nscl_evt::{NsclData, ParseError, RingItem, Skipped};
+
+fn good(n: u8) -> Vec<u8> {
+    item(30, &NO_BODY_HEADER, &[n, 0, 0, 0])
+}
+
+fn bodies(data: &[u8], skips: &mut Vec<Skipped>) -> Vec<u8> {
+    NsclData::new(data)
+        .resync(|x| skips.push(x))
+        .map(|e| e.ring_item().unwrap().bytes()[0])
+        .collect()
+}
+
+#[test]
+fn skips_bad_regions() {
+    let mut data = good(1);
+    data.extend([0u8; 7]);
+    data.extend(good(2));
+    data.extend([5u8, 0, 0, 0, 1, 1]);
+    data.extend(good(3));
+    let mut skips = Vec::new();
+    assert_eq!(bodies(&data, &mut skips), vec![1, 2, 3]);
+    assert_eq!(skips.len(), 2);
+    assert_eq!(skips[0].range, 16..23);
+    assert_eq!(skips[0].error, ParseError::ZeroSize);
+    assert_eq!(skips[1].range, 39..45);
+}
+
+#[test]
+fn unknown_types_are_kept() {
+    let mut data = good(1);
+    data.extend(item(777, &NO_BODY_HEADER, &[9, 0, 0, 0]));
+    data.extend(good(2));
+    let mut skips = Vec::new();
+    let events: Vec<_> = NsclData::new(&data).resync(|x| skips.push(x)).collect();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        events[1].ring_item().unwrap(),
+        RingItem::Unknown { type_id: 777, .. }
+    ));
+    assert!(skips.is_empty());
+}
+
+#[test]
+fn unknown_types_are_not_found_by_skipping() {
+    let mut data = good(1);
+    data.extend([0u8; 5]);
+    data.extend(item(777, &NO_BODY_HEADER, &[9, 0, 0, 0]));
+    data.extend(good(2));
+    let mut skips = Vec::new();
+    assert_eq!(bodies(&data, &mut skips), vec![1, 2]);
+    assert_eq!(skips.len(), 1);
+    assert_eq!(skips[0].range, 16..37);
+}
+
+#[test]
+fn garbage_that_parses_is_skipped() {
+    // 12 bytes that look like the start of a 40 byte item of type 777
+    let mut data = words32(&[40, 777, 0]);
+    for n in 1..=4 {
+        data.extend(good(n));
+    }
+    let mut skips = Vec::new();
+    let bodies: Vec<_> = NsclData::new(&data)
+        .resync(|x| skips.push(x))
+        .known_types_only(true)
+        .map(|e| e.ring_item().unwrap().bytes()[0])
+        .collect();
+    assert_eq!(bodies, vec![1, 2, 3, 4]);
+    assert_eq!(
+        skips,
+        vec![Skipped {
+            range: 0..12,
+            error: ParseError::UnknownType(777)
+        }]
+    );
+}
+
+#[test]
+fn trailing_garbage_is_skipped() {
+    let mut data = good(1);
+    data.extend([1, 2, 3]);
+    let mut skips = Vec::new();
+    assert_eq!(bodies(&data, &mut skips), vec![1]);
+    assert_eq!(skips[0].range, 16..19);
+}
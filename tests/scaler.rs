@@ -0,0 +1,145 @@
+use nscl_evt::{
+    scaler::{Accumulator, ChannelConfig, ChannelMap},
+    writer::{BodyHeaderBuilder, PeriodicScalersBuilder, RingFormatBuilder, StateChangeBuilder},
+    FormatVersion, NsclData, RingItemWriter,
+};
+
+/// A run from source 7, with a readout for each of `readouts`.
+fn run(readouts: &[PeriodicScalersBuilder]) -> Vec<u8> {
+    let mut w = RingItemWriter::new(Vec::new());
+    w.write(&RingFormatBuilder::new(FormatVersion::V11))
+        .unwrap();
+    let bh = BodyHeaderBuilder::new(0, 7);
+    w.write_with_body_header(&bh, &StateChangeBuilder::begin_run(1))
+        .unwrap();
+    for readout in readouts {
+        w.write_with_body_header(&bh, readout).unwrap();
+    }
+    w.into_inner()
+}
+
+#[test]
+fn rollover() {
+    let data = run(&[
+        PeriodicScalersBuilder::new(vec![0xff_fff0, 4_000_000_000])
+            .interval(0, 2, 1)
+            .is_incremental(false),
+        PeriodicScalersBuilder::new(vec![0x10, 100])
+            .interval(2, 4, 1)
+            .is_incremental(false),
+        PeriodicScalersBuilder::new(vec![0x100_0005, 7])
+            .interval(8, 9, 2)
+            .is_incremental(true),
+    ]);
+    let mut accumulator = Accumulator::new().channel_width(7, 0, 24);
+    let intervals: Vec<_> = NsclData::new(&data)
+        .map(|e| accumulator.observe(&e.unwrap()).unwrap())
+        .collect();
+    assert_eq!(intervals.len(), 5);
+    assert!(intervals[0].is_none());
+    assert!(intervals[1].is_none());
+
+    let interval = intervals[3].as_ref().unwrap();
+    assert_eq!(interval.source_id, 7);
+    assert_eq!(interval.duration(), 2.0);
+    assert_eq!(
+        interval.increments,
+        vec![0x20, 100 + (1u64 << 32) - 4_000_000_000]
+    );
+    assert_eq!(interval.rates().unwrap()[0], 16.0);
+
+    // Incremental readouts are masked to the width, too
+    let interval = intervals[4].as_ref().unwrap();
+    assert_eq!(interval.increments, vec![5, 7]);
+    assert_eq!(accumulator.total(7, 0), Some(0xff_fff0 + 0x20 + 5));
+    assert_eq!(accumulator.total(7, 1), Some((1u64 << 32) + 100 + 7));
+    assert_eq!(accumulator.rate(7, 1), Some(14.0));
+    assert_eq!(accumulator.total(7, 2), None);
+}
+
+#[test]
+fn empty_interval_has_no_rate() {
+    let data = run(&[PeriodicScalersBuilder::new(vec![3]).interval(5, 5, 1)]);
+    let mut accumulator = Accumulator::new();
+    let interval = NsclData::new(&data)
+        .find_map(|e| accumulator.observe(&e.unwrap()).unwrap())
+        .unwrap();
+    assert_eq!(interval.rates(), None);
+    assert_eq!(accumulator.rate(7, 0), None);
+    assert_eq!(accumulator.total(7, 0), Some(3));
+}
+
+#[test]
+fn begin_run_clears_readouts() {
+    let mut data = run(&[PeriodicScalersBuilder::new(vec![100])
+        .interval(0, 1, 1)
+        .is_incremental(false)]);
+    // A second run, whose counters start from 0 again
+    data.extend(
+        &run(&[PeriodicScalersBuilder::new(vec![30])
+            .interval(0, 1, 1)
+            .is_incremental(false)])[..],
+    );
+    let mut accumulator = Accumulator::new();
+    for e in NsclData::new(&data) {
+        accumulator.observe(&e.unwrap()).unwrap();
+    }
+    assert_eq!(accumulator.total(7, 0), Some(130));
+
+    // Without the begin run, the second readout looks like a rollover
+    let mut accumulator = Accumulator::new();
+    for e in NsclData::new(&data) {
+        let e = e.unwrap();
+        if e.ring_item().unwrap().as_begin_run().is_none() {
+            accumulator.observe(&e).unwrap();
+        }
+    }
+    assert_eq!(accumulator.total(7, 0), Some(100 + 30 + (1u64 << 32) - 100));
+}
+
+#[test]
+fn widths_from_channel_map() {
+    let data = run(&[
+        PeriodicScalersBuilder::new(vec![0xff, 0xff])
+            .interval(0, 1, 1)
+            .is_incremental(false),
+        PeriodicScalersBuilder::new(vec![0x01, 0x01])
+            .interval(1, 2, 1)
+            .is_incremental(false),
+    ]);
+    let names = ChannelMap::parse("7 0 narrow 8\n7 1 wide").unwrap();
+    let mut accumulator = Accumulator::new().width(16).channel_map(names);
+    for e in NsclData::new(&data) {
+        accumulator.observe(&e.unwrap()).unwrap();
+    }
+    assert_eq!(accumulator.total_by_name("narrow"), Some(0x101));
+    assert_eq!(accumulator.total_by_name("wide"), Some(0x1_0001));
+    assert_eq!(accumulator.name(7, 1), Some("wide"));
+}
+
+#[test]
+#[should_panic(expected = "scaler width 0 isn't in 1..=32")]
+fn zero_width() {
+    let _ = Accumulator::new().width(0);
+}
+
+#[test]
+#[should_panic(expected = "scaler width 33 isn't in 1..=32")]
+fn wide_channel_width() {
+    let _ = Accumulator::new().channel_width(7, 0, 33);
+}
+
+#[test]
+#[should_panic(expected = "scaler width 0 isn't in 1..=32")]
+fn zero_width_in_channel_map() {
+    let mut names = ChannelMap::new();
+    names.insert(
+        7,
+        0,
+        ChannelConfig {
+            name: "zero".to_string(),
+            width: Some(0),
+        },
+    );
+    let _ = Accumulator::new().channel_map(names);
+}
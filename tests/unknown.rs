@@ -0,0 +1,25 @@
+mod common;
+
+use common::{item, NO_BODY_HEADER};
+use nscl_evt::{NsclData, RingItem};
+
+#[test]
+fn unknown_types_are_kept() {
+    let mut data = item(99, &NO_BODY_HEADER, &[1, 2, 3]);
+    data.extend(item(30, &NO_BODY_HEADER, &[4, 5]));
+    let events: Vec<_> = NsclData::new(&data).map(Result::unwrap).collect();
+    assert_eq!(events.len(), 2);
+    let ri = events[0].ring_item().unwrap();
+    assert!(matches!(ri, RingItem::Unknown { type_id: 99, .. }));
+    assert_eq!(ri.bytes(), &[1, 2, 3]);
+    assert!(!RingItem::is_known_type(99));
+    assert!(events[1].ring_item().unwrap().as_physics_event().is_some());
+}
+
+#[test]
+fn user_items_are_not_unknown() {
+    let data = item(40000, &NO_BODY_HEADER, &[1]);
+    let event = NsclData::new(&data).next().unwrap().unwrap();
+    assert!(matches!(event.ring_item().unwrap(), RingItem::UserItem(_)));
+    assert!(RingItem::is_known_type(40000));
+}
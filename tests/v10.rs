@@ -0,0 +1,71 @@
+mod common;
+
+use common::{item, words32};
+use nscl_evt::{BodyHeader, FormatVersion, NsclData};
+
+fn begin_run(run: u32, title: &str) -> Vec<u8> {
+    let mut body = words32(&[run, 0, 1_000_000_000]);
+    body.extend(title.as_bytes());
+    body.resize(12 + 80, 0);
+    item(1, &[], &body)
+}
+
+#[test]
+fn layouts() {
+    let mut data = begin_run(7, "ten");
+    let mut body = words32(&[5, 1_000_000_005, 2]);
+    body.extend(b"set a 1\0set b 2\0");
+    data.extend(item(11, &[], &body));
+    data.extend(item(
+        20,
+        &[],
+        &words32(&[0, 10, 1_000_000_010, 2, 100, 200]),
+    ));
+    data.extend(item(31, &[], &words32(&[10, 1_000_000_010, 1234, 1])));
+    data.extend(item(30, &[], &[1, 2, 3, 4]));
+
+    let events: Vec<_> = NsclData::with_version(&data, FormatVersion::V10)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(events.len(), 5);
+    assert!(matches!(
+        events[0].body_header().unwrap(),
+        BodyHeader::Absent
+    ));
+
+    let ri = events[0].ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 7);
+    assert_eq!(ri.timestamp().unwrap(), 1_000_000_000);
+    assert_eq!(ri.offset_divisor().unwrap(), 1);
+    assert_eq!(ri.title().unwrap(), "ten");
+
+    let ri = events[1]
+        .ring_item()
+        .unwrap()
+        .as_monitored_variables()
+        .unwrap();
+    assert_eq!(ri.time_offset().unwrap(), 5);
+    assert_eq!(ri.offset_divisor().unwrap(), 1);
+    assert_eq!(ri.strings().unwrap(), vec!["set a 1", "set b 2"]);
+
+    let ri = events[2]
+        .ring_item()
+        .unwrap()
+        .as_periodic_scalers()
+        .unwrap();
+    assert_eq!(ri.interval_end_offset().unwrap(), 10);
+    assert_eq!(ri.interval_divisor().unwrap(), 1);
+    assert!(ri.is_incremental().unwrap());
+    assert_eq!(ri.scalers().unwrap(), vec![100, 200]);
+
+    let ri = events[3]
+        .ring_item()
+        .unwrap()
+        .as_physics_event_count()
+        .unwrap();
+    assert_eq!(ri.timestamp().unwrap(), 1_000_000_010);
+    assert_eq!(ri.event_count().unwrap(), (1 << 32) | 1234);
+
+    let ri = events[4].ring_item().unwrap();
+    assert_eq!(ri.bytes(), &[1, 2, 3, 4]);
+}
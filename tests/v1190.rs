@@ -0,0 +1,145 @@
+mod common;
+
+use common::words32;
+use nscl_evt::{
+    v1190::{self, Edge, Error, Events, Model},
+    writer::{Encode, RawBuilder},
+    FormatVersion, ParseError,
+};
+
+const FILLER: u32 = 0b11000 << 27;
+
+fn event_words() -> Vec<u32> {
+    vec![
+        (0b01000 << 27) | (17 << 5) | 4,
+        0b00001 << 27,
+        (3 << 19) | 1000,
+        (1 << 26) | (3 << 19) | 1500,
+        (0b00011 << 27) | 4,
+        (0b00100 << 27) | (1 << 24) | 0x10,
+        (0b10001 << 27) | 12345,
+        (0b10000 << 27) | (1 << 24) | (8 << 5) | 4,
+    ]
+}
+
+#[test]
+fn events() {
+    let mut words = vec![FILLER];
+    words.extend(event_words());
+    let events: Vec<_> = Events::new(words, Model::V1190)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(events.len(), 1);
+    let e = &events[0];
+    assert_eq!((e.geo, e.event_count), (4, 17));
+    assert_eq!(e.trigger_time_tag, Some(12345));
+    assert_eq!(e.channel(3).len(), 2);
+    assert_eq!(e.channel(3)[0].edge, Edge::Leading);
+    assert_eq!(e.channel(3)[0].value, 1000);
+    assert_eq!(e.channel(3)[1].edge, Edge::Trailing);
+    assert_eq!(e.channel(3)[1].value, 1500);
+    assert!(e.channel(4).is_empty());
+    assert_eq!(e.tdc_errors.len(), 1);
+    assert_eq!((e.tdc_errors[0].tdc, e.tdc_errors[0].flags), (1, 0x10));
+    assert!(e.tdc_error);
+    assert!(!e.overflow);
+    assert!(!e.trigger_lost);
+}
+
+#[test]
+fn v1290_measurements() {
+    let words = [
+        (0b01000 << 27) | (1 << 5) | 2,
+        (5 << 21) | 0x1f_ffff,
+        (0b10000 << 27) | (3 << 5) | 2,
+    ];
+    let e = Events::new(words, Model::V1290).next().unwrap().unwrap();
+    assert_eq!(e.channel(5)[0].value, 0x1f_ffff);
+}
+
+#[test]
+fn errors() {
+    let words = event_words();
+    assert_eq!(
+        Events::new(words[..4].iter().copied(), Model::V1190).next(),
+        Some(Err(Error::MissingTrailer))
+    );
+
+    let mut bad = words.clone();
+    bad[7] = (0b10000 << 27) | (9 << 5) | 4;
+    assert_eq!(
+        Events::new(bad, Model::V1190).next(),
+        Some(Err(Error::WordCountMismatch {
+            trailer: 9,
+            words: 8
+        }))
+    );
+
+    let mut bad = words.clone();
+    bad[4] = (0b00011 << 27) | 5;
+    assert_eq!(
+        Events::new(bad, Model::V1190).next(),
+        Some(Err(Error::WordCountMismatch {
+            trailer: 5,
+            words: 4
+        }))
+    );
+
+    let mut bad = words.clone();
+    bad[7] = (0b10000 << 27) | (8 << 5) | 5;
+    assert_eq!(
+        Events::new(bad, Model::V1190).next(),
+        Some(Err(Error::GeoMismatch {
+            header: 4,
+            trailer: 5
+        }))
+    );
+
+    // An error ends the iteration
+    let mut bad = vec![1000];
+    bad.extend(&words);
+    let results: Vec<_> = Events::new(bad, Model::V1190).collect();
+    assert_eq!(results, vec![Err(Error::UnexpectedWord(1000))]);
+}
+
+#[test]
+fn physics_event() {
+    let mut words = vec![0];
+    words.extend(event_words());
+    words.push(FILLER);
+    words[0] = 2 * words.len() as u32;
+    // Anything past the size isn't part of the data
+    words.push(0xdead_beef);
+    let e = RawBuilder::physics_event(words32(&words))
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let events: Vec<_> = v1190::events(&ri, Model::V1190).unwrap().collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_ref().unwrap().event_count, 17);
+}
+
+#[test]
+fn truncated_physics_event() {
+    // The size claims more words than there are
+    let e = RawBuilder::physics_event(words32(&[100, FILLER]))
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(
+        v1190::events(&ri, Model::V1190).err(),
+        Some(ParseError::OutOfBounds {
+            offset: 4,
+            len: 196
+        })
+    );
+
+    let e = RawBuilder::physics_event(vec![1, 2])
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert!(matches!(
+        v1190::events(&ri, Model::V1190),
+        Err(ParseError::OutOfBounds { .. })
+    ));
+}
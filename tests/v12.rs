@@ -0,0 +1,82 @@
+mod common;
+
+use common::{item, words32, EMPTY_BODY_HEADER};
+use nscl_evt::{FormatVersion, NsclData};
+
+fn ring_format() -> Vec<u8> {
+    item(12, &EMPTY_BODY_HEADER, &[12, 0, 0, 0])
+}
+
+#[test]
+fn original_sids() {
+    let mut data = ring_format();
+    let mut body = words32(&[7, 1, 1_600_000_000, 1, 99]);
+    body.extend(b"twelve");
+    body.resize(body.len() + 75, 0);
+    data.extend(item(1, &EMPTY_BODY_HEADER, &body));
+    let mut body = words32(&[5, 1_600_000_005, 1, 1, 98]);
+    body.extend(b"set a 1\0");
+    data.extend(item(11, &EMPTY_BODY_HEADER, &body));
+    data.extend(item(
+        20,
+        &EMPTY_BODY_HEADER,
+        &words32(&[0, 10, 1_600_000_010, 1, 2, 1, 97, 100, 200]),
+    ));
+    data.extend(item(
+        31,
+        &EMPTY_BODY_HEADER,
+        &words32(&[10, 1, 96, 1_600_000_010, 1234, 0]),
+    ));
+
+    let d = NsclData::new(&data);
+    assert_eq!(d.version(), FormatVersion::V12);
+    let events: Vec<_> = d.map(Result::unwrap).collect();
+    assert_eq!(events.len(), 5);
+    assert_eq!(events[1].body_header().unwrap().size(), 4);
+    assert_eq!(events[1].body_header().unwrap().source_id(), None);
+
+    let ri = events[1].ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 7);
+    assert_eq!(ri.timestamp().unwrap(), 1_600_000_000);
+    assert_eq!(ri.original_sid().unwrap(), Some(99));
+    assert_eq!(ri.title().unwrap(), "twelve");
+
+    let ri = events[2]
+        .ring_item()
+        .unwrap()
+        .as_monitored_variables()
+        .unwrap();
+    assert_eq!(ri.time_offset().unwrap(), 5);
+    assert_eq!(ri.original_sid().unwrap(), Some(98));
+    assert_eq!(ri.strings().unwrap(), vec!["set a 1"]);
+
+    let ri = events[3]
+        .ring_item()
+        .unwrap()
+        .as_periodic_scalers()
+        .unwrap();
+    assert_eq!(ri.interval_end_offset().unwrap(), 10);
+    assert!(ri.is_incremental().unwrap());
+    assert_eq!(ri.original_sid().unwrap(), Some(97));
+    assert_eq!(ri.scalers().unwrap(), vec![100, 200]);
+
+    let ri = events[4]
+        .ring_item()
+        .unwrap()
+        .as_physics_event_count()
+        .unwrap();
+    assert_eq!(ri.original_sid().unwrap(), Some(96));
+    assert_eq!(ri.timestamp().unwrap(), 1_600_000_010);
+    assert_eq!(ri.event_count().unwrap(), 1234);
+}
+
+#[test]
+fn ring_format_switches_version() {
+    let mut data = ring_format();
+    data.extend(item(30, &EMPTY_BODY_HEADER, &[1, 2]));
+    let mut d = NsclData::with_version(&data, FormatVersion::V11);
+    d.next().unwrap().unwrap();
+    assert_eq!(d.version(), FormatVersion::V12);
+    let e = d.next().unwrap().unwrap();
+    assert_eq!(e.ring_item().unwrap().bytes(), &[1, 2]);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{built, fragment, physics_event};
+use nscl_evt::{FragmentWalker, OwnedEvent};
+
+/// An event built from two levels of event builders.
+fn chained() -> OwnedEvent {
+    let a = physics_event(10, 1, vec![1, 2, 3, 4]);
+    let b = physics_event(11, 2, vec![5, 6]);
+    let c = physics_event(12, 3, vec![7]);
+    let inner = physics_event(
+        10,
+        50,
+        built(&[fragment(10, 1, a.bytes()), fragment(11, 2, b.bytes())]),
+    );
+    physics_event(
+        10,
+        99,
+        built(&[fragment(10, 50, inner.bytes()), fragment(12, 3, c.bytes())]),
+    )
+}
+
+fn source_ids(walker: FragmentWalker, event: &OwnedEvent) -> Vec<Vec<Option<u32>>> {
+    walker
+        .walk(event.as_ref())
+        .unwrap()
+        .iter()
+        .map(|x| x.path.iter().map(|x| x.source_id).collect())
+        .collect()
+}
+
+#[test]
+fn leaves() {
+    let e = chained();
+    assert_eq!(
+        source_ids(FragmentWalker::new(), &e),
+        vec![
+            vec![Some(99), Some(50), Some(1)],
+            vec![Some(99), Some(50), Some(2)],
+            vec![Some(99), Some(3)],
+        ]
+    );
+    let leaves = FragmentWalker::new().walk(e.as_ref()).unwrap();
+    assert_eq!(leaves[1].path[2].timestamp, Some(11));
+    assert_eq!(leaves[1].event.ring_item().unwrap().bytes(), &[5, 6]);
+}
+
+#[test]
+fn max_depth() {
+    let e = chained();
+    assert_eq!(
+        source_ids(FragmentWalker::new().max_depth(1), &e),
+        vec![vec![Some(99), Some(50)], vec![Some(99), Some(3)]]
+    );
+    assert_eq!(
+        source_ids(FragmentWalker::new().max_depth(0), &e),
+        vec![vec![Some(99)]]
+    );
+}
+
+#[test]
+fn unbuilt_event_is_a_leaf() {
+    // The body size is right, but the rest isn't a fragment
+    let e = physics_event(10, 7, vec![8, 0, 0, 0, 1, 2, 3, 4]);
+    assert_eq!(source_ids(FragmentWalker::new(), &e), vec![vec![Some(7)]]);
+}
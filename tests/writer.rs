@@ -0,0 +1,223 @@
+use nscl_evt::{
+    writer::{
+        encode, BodyHeaderBuilder, Encode, EvbGlomInfoBuilder, PeriodicScalersBuilder,
+        PhysicsEventCountBuilder, RawBuilder, RingFormatBuilder, StateChangeBuilder, TextBuilder,
+    },
+    FormatVersion, NsclData, RingItem, RingItemWriter,
+};
+
+/// Write an item with a body header, if the format has them.
+fn write_with_body_header(
+    w: &mut RingItemWriter<Vec<u8>>,
+    body_header: &BodyHeaderBuilder,
+    item: &dyn Encode,
+) {
+    if w.version() == FormatVersion::V10 {
+        w.write(item).unwrap();
+    } else {
+        w.write_with_body_header(body_header, item).unwrap();
+    }
+}
+
+fn write_all(version: FormatVersion) -> Vec<u8> {
+    let mut w = RingItemWriter::with_version(Vec::new(), version);
+    if version != FormatVersion::V10 {
+        w.write(&RingFormatBuilder::new(version)).unwrap();
+    }
+    w.write(
+        &StateChangeBuilder::begin_run(42)
+            .title("a title")
+            .timestamp(1_600_000_000)
+            .original_sid(3),
+    )
+    .unwrap();
+    w.write(
+        &TextBuilder::monitored_variables()
+            .time_offset(10, 2)
+            .string("set a 1")
+            .string("set b 2"),
+    )
+    .unwrap();
+    write_with_body_header(
+        &mut w,
+        &BodyHeaderBuilder::new(1234, 5),
+        &PeriodicScalersBuilder::new(vec![1, 2, 3])
+            .interval(0, 10, 1)
+            .is_incremental(false),
+    );
+    write_with_body_header(
+        &mut w,
+        &BodyHeaderBuilder::new(5678, 6).extension(vec![0xaa; 4]),
+        &RawBuilder::physics_event(vec![1, 2, 3, 4]),
+    );
+    w.write(&PhysicsEventCountBuilder::new(1 << 40).timestamp(7))
+        .unwrap();
+    w.write(&EvbGlomInfoBuilder::new(100, true, 2)).unwrap();
+    w.write(&StateChangeBuilder::end_run(42).time_offset(20, 1))
+        .unwrap();
+    w.into_inner()
+}
+
+fn check_all(version: FormatVersion, data: &[u8]) {
+    let d = NsclData::new(data);
+    assert_eq!(d.version(), version);
+    let mut items = d.map(Result::unwrap).collect::<Vec<_>>();
+    if version != FormatVersion::V10 {
+        let ri = items.remove(0).ring_item().unwrap();
+        assert_eq!(
+            ri.as_ring_format().unwrap().major().unwrap(),
+            match version {
+                FormatVersion::V10 => 10,
+                FormatVersion::V11 => 11,
+                FormatVersion::V12 => 12,
+            }
+        );
+    }
+    assert_eq!(items.len(), 7);
+
+    let ri = items[0].ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.run_number().unwrap(), 42);
+    assert_eq!(ri.timestamp().unwrap(), 1_600_000_000);
+    assert_eq!(ri.title().unwrap(), "a title");
+    assert_eq!(ri.title_bytes().unwrap().len(), 80);
+    let sid = (version == FormatVersion::V12).then_some(3);
+    assert_eq!(ri.original_sid().unwrap(), sid);
+
+    let ri = items[1]
+        .ring_item()
+        .unwrap()
+        .as_monitored_variables()
+        .unwrap();
+    assert_eq!(ri.time_offset().unwrap(), 10);
+    assert_eq!(ri.strings().unwrap(), vec!["set a 1", "set b 2"]);
+
+    let bh = items[2].body_header().unwrap();
+    let ri = items[2].ring_item().unwrap().as_periodic_scalers().unwrap();
+    assert_eq!(ri.scalers().unwrap(), vec![1, 2, 3]);
+    assert_eq!(ri.interval_end_offset().unwrap(), 10);
+    if version == FormatVersion::V10 {
+        assert_eq!(bh.source_id(), None);
+    } else {
+        assert_eq!(bh.timestamp(), Some(1234));
+        assert_eq!(bh.source_id(), Some(5));
+        assert!(!ri.is_incremental().unwrap());
+    }
+
+    let bh = items[3].body_header().unwrap();
+    let ri = items[3].ring_item().unwrap().as_physics_event().unwrap();
+    assert_eq!(ri.bytes(), &[1, 2, 3, 4]);
+    if version != FormatVersion::V10 {
+        assert_eq!(bh.extension(), Some(&[0xaa; 4][..]));
+    }
+
+    let ri = items[4]
+        .ring_item()
+        .unwrap()
+        .as_physics_event_count()
+        .unwrap();
+    assert_eq!(ri.event_count().unwrap(), 1 << 40);
+    assert_eq!(ri.timestamp().unwrap(), 7);
+
+    let ri = items[5].ring_item().unwrap().as_evb_glom_info().unwrap();
+    assert_eq!(ri.coincident_ticks().unwrap(), 100);
+    assert!(ri.is_building().unwrap());
+    assert_eq!(ri.timestamp_policy().unwrap(), 2);
+
+    let ri = items[6].ring_item().unwrap().as_end_run().unwrap();
+    assert_eq!(ri.time_offset().unwrap(), 20);
+    assert_eq!(ri.title().unwrap(), "");
+}
+
+#[test]
+fn round_trip_v10() {
+    check_all(FormatVersion::V10, &write_all(FormatVersion::V10));
+}
+
+#[test]
+fn round_trip_v11() {
+    check_all(FormatVersion::V11, &write_all(FormatVersion::V11));
+}
+
+#[test]
+fn round_trip_v12() {
+    check_all(FormatVersion::V12, &write_all(FormatVersion::V12));
+}
+
+#[test]
+fn long_title_is_cut_short() {
+    let title = "é".repeat(50);
+    let e = StateChangeBuilder::begin_run(1)
+        .title(&title)
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap();
+    assert_eq!(ri.as_begin_run().unwrap().title().unwrap(), "é".repeat(40));
+}
+
+#[test]
+fn longest_title_is_terminated() {
+    let title = "x".repeat(100);
+    let e = StateChangeBuilder::begin_run(1)
+        .title(&title)
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_begin_run().unwrap();
+    assert_eq!(ri.title().unwrap(), "x".repeat(80));
+    // The title is followed by a NUL, as in NSCLDAQ
+    assert_eq!(ri.bytes().len(), 16 + 81);
+    assert_eq!(ri.bytes().last(), Some(&0));
+}
+
+#[test]
+fn body_header_is_rejected_for_v10() {
+    let mut w = RingItemWriter::with_version(Vec::new(), FormatVersion::V10);
+    let e = w
+        .write_with_body_header(
+            &BodyHeaderBuilder::new(1, 2),
+            &RawBuilder::physics_event(vec![1, 2]),
+        )
+        .unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(w.into_inner().is_empty());
+
+    let item = RawBuilder::physics_event(vec![1, 2]);
+    let e = item
+        .to_event(Some(&BodyHeaderBuilder::new(1, 2)), FormatVersion::V10)
+        .unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    let mut out = vec![7];
+    let e = encode(
+        &mut out,
+        Some(&BodyHeaderBuilder::new(1, 2)),
+        &item,
+        FormatVersion::V10,
+    )
+    .unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(out, vec![7]);
+}
+
+#[test]
+fn strings_are_split_at_nuls() {
+    let e = TextBuilder::monitored_variables()
+        .string("set a 1\0set b 2")
+        .string("set c 3")
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_monitored_variables().unwrap();
+    assert_eq!(ri.string_count().unwrap(), 3);
+    assert_eq!(ri.strings().unwrap(), vec!["set a 1", "set b 2", "set c 3"]);
+}
+
+#[test]
+fn copied_events_round_trip() {
+    let data = write_all(FormatVersion::V11);
+    let mut w = RingItemWriter::new(Vec::new());
+    for e in NsclData::new(&data).map(Result::unwrap) {
+        if !matches!(e.ring_item().unwrap(), RingItem::PhysicsEvent(_)) {
+            w.write_event(&e).unwrap();
+        }
+    }
+    let filtered = w.into_inner();
+    assert_eq!(NsclData::new(&filtered).count(), 7);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::words16;
+use nscl_evt::{
+    writer::{Encode, RawBuilder},
+    xxusb::{self, CamacDatum, Error, Segments, Stack, Stacks},
+    FormatVersion, ParseError,
+};
+
+fn segment_header(stack_id: u16, continued: bool, words: u16) -> u16 {
+    (stack_id << 13) | ((continued as u16) << 12) | words
+}
+
+fn split_stack() -> Vec<u16> {
+    vec![
+        segment_header(1, true, 2),
+        0x1234,
+        0xffff,
+        segment_header(1, false, 3),
+        0x0005,
+        0x0300,
+        0xffff,
+    ]
+}
+
+#[test]
+fn segments() {
+    let data = words16(&split_stack());
+    let segments: Vec<_> = Segments::new(&data).map(Result::unwrap).collect();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].stack_id, 1);
+    assert!(segments[0].continued);
+    assert_eq!(segments[0].data, &[0x34, 0x12, 0xff, 0xff]);
+    assert!(!segments[1].continued);
+}
+
+#[test]
+fn stacks() {
+    let mut words = split_stack();
+    words.extend([segment_header(2, false, 1), 7]);
+    let data = words16(&words);
+    let stacks: Vec<_> = Stacks::new(&data).map(Result::unwrap).collect();
+    assert_eq!(stacks.len(), 2);
+    assert_eq!(stacks[0].stack_id, 1);
+    assert_eq!(
+        stacks[0].words16().collect::<Vec<_>>(),
+        vec![0x1234, 0xffff, 5, 0x300, 0xffff]
+    );
+    assert_eq!(stacks[1].stack_id, 2);
+    assert_eq!(stacks[1].cursor().read_u16().unwrap(), 7);
+
+    let parts = stacks[0].split(0xffff);
+    assert_eq!(parts, vec![&[0x34, 0x12][..], &[5, 0, 0, 3][..]]);
+}
+
+#[test]
+fn camac() {
+    let stack = Stack {
+        stack_id: 0,
+        data: words16(&[0x0005, 0x0300, 0xabcd, 0x0012]),
+    };
+    let reads: Vec<_> = stack.camac().collect();
+    assert_eq!(
+        reads,
+        vec![
+            CamacDatum {
+                value: 5,
+                q: true,
+                x: true
+            },
+            CamacDatum {
+                value: 0x12_abcd,
+                q: false,
+                x: false
+            },
+        ]
+    );
+    assert_eq!(stack.words32().count(), 2);
+}
+
+#[test]
+fn errors() {
+    let words = split_stack();
+    assert_eq!(
+        Stacks::new(&words16(&words[..3])).next(),
+        Some(Err(Error::MissingContinuation))
+    );
+    let mut bad = words.clone();
+    bad[3] = segment_header(2, false, 3);
+    assert_eq!(
+        Stacks::new(&words16(&bad)).next(),
+        Some(Err(Error::StackMismatch {
+            first: 1,
+            segment: 2
+        }))
+    );
+    let results: Vec<_> = Stacks::new(&words16(&words[..2])).collect();
+    assert!(matches!(
+        results[..],
+        [Err(Error::Parse(ParseError::OutOfBounds { .. }))]
+    ));
+}
+
+#[test]
+fn physics_event() {
+    let e = RawBuilder::physics_event(words16(&split_stack()))
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    let stack = xxusb::stack(&ri).unwrap();
+    assert_eq!(stack.data.len(), 10);
+
+    let e = RawBuilder::physics_event(vec![])
+        .to_event(None, FormatVersion::V11)
+        .unwrap();
+    let ri = e.ring_item().unwrap().as_physics_event().unwrap();
+    assert!(xxusb::stack(&ri).is_err());
+}